@@ -0,0 +1,120 @@
+//! Timing the ROM's `memcpy` against the compiler's, to decide whether the
+//! ROM function is worth using for a given copy.
+//!
+//! The RP2040's Cortex-M0+ cores have no DWT cycle counter, so each call is
+//! timed with SysTick, clocked from the processor clock, and the cost of
+//! reading SysTick is measured and subtracted.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use core::hint::black_box;
+use core::ptr::{read_volatile, write_volatile};
+
+use crate::{MemOps, RomFunctionGroup};
+
+/// SysTick registers
+const SYST_CSR: *mut u32 = 0xe000_e010 as *mut u32;
+const SYST_RVR: *mut u32 = 0xe000_e014 as *mut u32;
+const SYST_CVR: *mut u32 = 0xe000_e018 as *mut u32;
+
+/// SysTick CSR bits: enable the counter, clocked from the processor clock
+const SYST_CSR_ENABLE: u32 = 1 << 0;
+const SYST_CSR_CLKSOURCE: u32 = 1 << 2;
+
+/// SysTick is a 24 bit down counter
+const SYST_MAX: u32 = 0x00ff_ffff;
+
+/// Returns the average processor clock cycles taken by the ROM's `memcpy` to
+/// copy `src` to `dst`, over `iters` calls.
+///
+/// Compare with [`bench_copy_from_slice`], which times the compiler's
+/// `memcpy`, over the same buffers.  The results depend heavily on the
+/// alignment of the source and destination, and on the length: the ROM
+/// `memcpy` copies a word at a time only when both are word aligned, and has
+/// a fixed setup cost which dominates short copies.  Where the buffers are
+/// in XIP flash, cache misses also add to the first iteration.  Benchmark
+/// the buffers, and the lengths, the application actually uses.
+///
+/// # Panics
+///
+/// If `iters` is 0, `src` and `dst` have different lengths, or the ROM's
+/// `memcpy` can't be found.
+///
+/// # Safety
+///
+/// SysTick is reconfigured for the duration of the call, and its current
+/// value is lost, so nothing else, such as an RTOS tick, may be using it.
+/// Interrupts should be disabled, or their handlers' cycles are included.
+pub unsafe fn bench_memcpy(src: &[u8], dst: &mut [u8], iters: u32) -> u32 {
+    assert_eq!(src.len(), dst.len(), "src and dst lengths must match");
+    let mem_ops = MemOps::lookup().expect("ROM memcpy not found");
+
+    time_calls(iters, || {
+        mem_ops.memcpy(
+            black_box(dst.as_mut_ptr()),
+            black_box(src.as_ptr()),
+            src.len() as u32,
+        );
+    })
+}
+
+/// Returns the average processor clock cycles taken by
+/// [`slice::copy_from_slice`], which uses the compiler's `memcpy`, to copy
+/// `src` to `dst`, over `iters` calls.
+///
+/// For comparison with [`bench_memcpy`].
+///
+/// # Panics
+///
+/// If `iters` is 0, or `src` and `dst` have different lengths.
+///
+/// # Safety
+///
+/// As for [`bench_memcpy`].
+pub unsafe fn bench_copy_from_slice(src: &[u8], dst: &mut [u8], iters: u32) -> u32 {
+    assert_eq!(src.len(), dst.len(), "src and dst lengths must match");
+
+    time_calls(iters, || {
+        black_box(&mut *dst).copy_from_slice(black_box(src));
+    })
+}
+
+// Time `iters` calls to `f` with SysTick, returning the average cycles per
+// call, less the cost of reading SysTick.  Each call is timed separately,
+// so the total may exceed SysTick's 24 bits, but no single call may.
+unsafe fn time_calls(iters: u32, mut f: impl FnMut()) -> u32 {
+    assert!(iters > 0, "iters must be non-zero");
+
+    let csr = read_volatile(SYST_CSR);
+    let rvr = read_volatile(SYST_RVR);
+
+    write_volatile(SYST_CSR, 0);
+    write_volatile(SYST_RVR, SYST_MAX);
+    // Any write clears the current value
+    write_volatile(SYST_CVR, 0);
+    write_volatile(SYST_CSR, SYST_CSR_ENABLE | SYST_CSR_CLKSOURCE);
+
+    // SysTick counts down, so the elapsed cycles are start - end, modulo
+    // 24 bits
+    let elapsed = |start: u32, end: u32| start.wrapping_sub(end) & SYST_MAX;
+
+    let start = read_volatile(SYST_CVR);
+    let overhead = elapsed(start, read_volatile(SYST_CVR));
+
+    let mut total: u64 = 0;
+    for _ in 0..iters {
+        let start = read_volatile(SYST_CVR);
+        f();
+        let end = read_volatile(SYST_CVR);
+        total += elapsed(start, end).saturating_sub(overhead) as u64;
+    }
+
+    write_volatile(SYST_CSR, 0);
+    write_volatile(SYST_RVR, rvr);
+    write_volatile(SYST_CVR, 0);
+    write_volatile(SYST_CSR, csr);
+
+    (total / iters as u64) as u32
+}
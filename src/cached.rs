@@ -0,0 +1,99 @@
+//! Cached ROM function pointers.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use core::marker::PhantomData;
+use portable_atomic::{AtomicPtr, Ordering};
+
+use crate::{RomError, ROM};
+
+/// A ROM function pointer which is looked up on first use, and cached.
+///
+/// Walking the ROM function table on every call adds overhead in tight
+/// loops.  A `CachedRomFn` does the lookup once, and stores the result
+/// atomically, so it can be placed in a `static`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rp2040_rom::CachedRomFn;
+///
+/// type Popcount32Fn = unsafe extern "C" fn(u32) -> u32;
+/// static POPCOUNT32: CachedRomFn<Popcount32Fn> = CachedRomFn::new();
+///
+/// unsafe {
+///     let popcount32 = POPCOUNT32.get((b'P', b'3')).unwrap();
+///     assert_eq!(popcount32(0xF0), 4);
+/// }
+/// ```
+pub struct CachedRomFn<F> {
+    cell: AtomicPtr<()>,
+    _marker: PhantomData<F>,
+}
+
+// Safety: the cell is only accessed atomically, and F is a function pointer
+unsafe impl<F> Sync for CachedRomFn<F> {}
+
+impl<F> CachedRomFn<F> {
+    /// Creates an empty cache.
+    pub const fn new() -> Self {
+        Self {
+            cell: AtomicPtr::new(core::ptr::null_mut()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<F> core::fmt::Debug for CachedRomFn<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CachedRomFn")
+            .field("ptr", &self.cell.load(Ordering::Relaxed))
+            .finish()
+    }
+}
+
+impl<F> Default for CachedRomFn<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F: Copy> CachedRomFn<F> {
+    /// Returns the function, looking it up in the ROM function table on the
+    /// first call.
+    ///
+    /// # Parameters
+    ///
+    /// * `code` - The two character code for the function
+    ///
+    /// # Returns
+    ///
+    /// The function, or [`RomError::FunctionNotFound`] if the code isn't
+    /// present in the function table.  A failed lookup isn't cached, so will
+    /// be retried on the next call.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::lookup_func`].  The same `code` must be passed on every
+    /// call.
+    pub unsafe fn get(&self, code: (u8, u8)) -> Result<F, RomError> {
+        let mut ptr = self.cell.load(Ordering::Relaxed);
+        if ptr.is_null() {
+            // Two contexts racing here will both store the same pointer, so
+            // no compare-and-swap is required
+            ptr = Self::lookup(code)?;
+            self.cell.store(ptr, Ordering::Relaxed);
+        }
+
+        Ok(core::mem::transmute_copy(&ptr))
+    }
+
+    fn lookup(code: (u8, u8)) -> Result<*mut (), RomError> {
+        // Safety: get()'s caller upholds lookup_func's requirements
+        unsafe { ROM::lookup_func::<F>(code) }
+            .map(|func| func.as_ptr() as *mut ())
+            .ok_or(RomError::FunctionNotFound { code })
+    }
+}
@@ -0,0 +1,100 @@
+//! Overriding the address the ROM is read from, for emulators and ROM
+//! research.
+//!
+//! On real silicon the ROM is at address 0, and the base must stay 0.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// The address the ROM is mapped at
+static ROM_BASE: AtomicUsize = AtomicUsize::new(0);
+
+/// Sets the address the ROM is mapped at, for emulators and ROM images which
+/// don't map it at address 0.
+///
+/// The base is added to every read this crate makes from the ROM header and
+/// tables - the magic, version, table pointers and the lookup function
+/// pointer - and to the 16-bit pointers read from it.  Pointers returned by
+/// the ROM's own lookup function, and the pointers in the ROM tables, are
+/// used unchanged, so the image itself must be built for its base.
+///
+/// The setting is global, affecting every caller of this crate, on both
+/// cores.
+///
+/// # Safety
+///
+/// On real silicon, the base must be left at the default of 0.  Otherwise
+/// `base` must be the address of a valid ROM image, which remains mapped
+/// for the rest of the program, and this must not be called concurrently
+/// with any other function in this crate.
+pub unsafe fn set_rom_base(base: usize) {
+    ROM_BASE.store(base, Ordering::Relaxed);
+}
+
+// Returns the address the ROM is mapped at
+pub(crate) fn rom_base() -> usize {
+    ROM_BASE.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LookupError, PlatformError, BOOTROM_MAGIC, ROM};
+
+    // A single test, as the base is global
+    #[test]
+    fn header_reads_use_base() {
+        let mut image = [0u8; 0x20];
+        image[0x10..0x13].copy_from_slice(b"ABC");
+        unsafe { set_rom_base(image.as_ptr() as usize) };
+        assert!(!ROM::rom_magic_valid());
+        assert_eq!(
+            ROM::platform_check(),
+            Err(PlatformError::BadMagic { found: 0x0043_4241 })
+        );
+
+        image[0x10..0x13].copy_from_slice(&BOOTROM_MAGIC);
+        unsafe { set_rom_base(image.as_ptr() as usize) };
+        assert!(ROM::rom_magic_valid());
+        #[cfg(feature = "rp2350")]
+        assert_eq!(ROM::rom_abi(), crate::RomAbi::Rp2350);
+        assert_eq!(
+            ROM::platform_check(),
+            Err(PlatformError::UnsupportedVersion(0))
+        );
+
+        image[0x13] = 3;
+        unsafe { set_rom_base(image.as_ptr() as usize) };
+        assert_eq!(ROM::rom_version(), 3);
+
+        // Null table pointers are caught before calling into the image
+        #[cfg(not(feature = "rp2350"))]
+        {
+            let lookup = unsafe { ROM::try_rom_func_lookup((b'U', b'B')) };
+            assert_eq!(lookup, Err(LookupError::TableUnreachable));
+            image[0x14] = 0x20;
+        }
+        unsafe { set_rom_base(image.as_ptr() as usize) };
+        let lookup = unsafe { ROM::try_rom_func_lookup((b'U', b'B')) };
+        assert_eq!(lookup, Err(LookupError::LookupHelperMissing));
+
+        // The pre-init cache holds the functions of the ROM at address 0, so
+        // isn't used with a custom base, and the header is still checked
+        #[cfg(feature = "pre-init")]
+        unsafe {
+            crate::pre_init::set_cache(0x1235 as *mut core::ffi::c_void);
+            let lookup = ROM::try_rom_func_lookup(crate::rom_codes::CONNECT_INTERNAL_FLASH);
+            assert_eq!(lookup, Err(LookupError::LookupHelperMissing));
+            crate::pre_init::clear_cache();
+        }
+
+        let mut dump = [0u8; 0x20];
+        assert_eq!(unsafe { ROM::copy_bootrom(&mut dump) }, 0x20);
+        assert_eq!(dump, image);
+
+        unsafe { set_rom_base(0) };
+    }
+}
@@ -0,0 +1,1187 @@
+//! Flash access via the RP2040 ROM.
+//!
+//! The ROM provides the low level routines to talk to the external QSPI
+//! flash.  While these run, the flash is not available for execute-in-place
+//! (XIP), so any code running between `flash_exit_xip` and
+//! `flash_enter_cmd_xip` must not live in flash.  Functions in this module
+//! which need to do this are placed in the `.data` section, which standard
+//! RP2040 linker scripts copy to RAM at startup.
+//!
+//! [`ROM::flash_erase_ram`] and [`ROM::flash_program_ram`] are placed in a
+//! `.ram_text` section instead, which the user must locate in RAM - see the
+//! crate documentation.
+//!
+//! The single call wrappers, such as [`ROM::flash_exit_xip`], are also
+//! placed in `.data`, so a ROM function never returns into flash.  However
+//! each looks up its ROM function on entry, and the lookup code runs from
+//! flash, so calling one while XIP is disabled still faults.  Sequences
+//! which run with XIP disabled should use [`ROM::flash_erase_ram`],
+//! [`ROM::flash_program_ram`], [`FlashOps`](crate::FlashOps) or
+//! [`FlashAccess`](crate::FlashAccess), which resolve every pointer first.
+//!
+//! The complete sequences issue a DSB and an ISB after flushing the XIP
+//! cache, so no instructions fetched before the flush are executed after
+//! it.
+//!
+//! In debug builds, the complete sequences panic with "flash operations
+//! require interrupts disabled" if PRIMASK shows interrupts are enabled,
+//! before XIP is disabled, rather than leaving an interrupt handler to fault
+//! later.
+//!
+//! The higher level functions, such as [`ROM::flash_write`], are written
+//! against [`FlashBackend`], and are also provided as its methods, so code
+//! built on them can be tested on the host with the `mock` feature's
+//! `MockFlash`.
+//!
+//! Because the compiler may choose not to inline small helpers in
+//! unoptimized builds (placing them in flash instead), flash operations
+//! should be performed from code built with at least `opt-level = 1`.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use crate::{rom_codes, FlashAccess, RomError, ROM};
+
+// Direct flash commands, which program the SSI directly, and so are RP2040
+// specific
+#[cfg(not(feature = "rp2350"))]
+mod cmd;
+
+// Entry points for a probe-rs (CMSIS-Pack) flash algorithm
+#[cfg(feature = "flash-algo")]
+mod algo;
+
+#[cfg(feature = "flash-algo")]
+pub use algo::{EraseSector, FlashInit, FlashUninit, ProgramPage};
+
+// The backend the higher level flash functions are written against
+mod backend;
+
+#[cfg(feature = "mock")]
+pub use backend::MockFlash;
+pub use backend::{FlashBackend, RomFlashBackend};
+
+/// Start of the XIP window, where flash is mapped
+pub const XIP_BASE: u32 = 0x1000_0000;
+
+/// Start of the XIP cache's memory when it is used as SRAM, with the cache
+/// disabled
+#[cfg(not(feature = "rp2350"))]
+pub const XIP_SRAM_BASE: u32 = 0x1500_0000;
+#[cfg(feature = "rp2350")]
+pub const XIP_SRAM_BASE: u32 = 0x13ff_c000;
+
+/// Size of the XIP window, and so the largest usable flash
+const XIP_WINDOW_SIZE: usize = 16 * 1024 * 1024;
+
+/// End of the XIP window's cached and uncached aliases of flash
+const XIP_ALIASES_END: u32 = 0x1400_0000;
+
+/// Flash page size - the unit of programming
+pub const FLASH_PAGE_SIZE: u32 = 1 << 8;
+
+/// Flash sector size - the smallest unit of erase
+pub const FLASH_SECTOR_SIZE: u32 = 1 << 12;
+
+/// Flash block size - the largest unit of erase
+pub const FLASH_BLOCK_SIZE: u32 = 1 << 16;
+
+/// Standard 64KB block erase command
+pub const FLASH_BLOCK_ERASE_CMD: u8 = 0xD8;
+
+/// Errors returned by flash functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FlashError {
+    /// The flash returned a JEDEC ID which doesn't describe a usable device.
+    /// Contains the raw manufacturer, memory type and capacity bytes.
+    InvalidJedecId([u8; 3]),
+
+    /// The flash returned an all-zeros unique ID, which usually means no
+    /// flash is connected, or it doesn't support the unique ID command.
+    InvalidUniqueId,
+
+    /// The flash address or size isn't aligned as the operation requires -
+    /// to a 4096 byte sector for erase, or a 256 byte page for program.
+    Unaligned,
+
+    /// The CRC-32 of the flash contents after programming doesn't match the
+    /// CRC-32 of the data programmed.
+    CrcMismatch { expected: u32, found: u32 },
+
+    /// A sector still wasn't fully erased after the maximum number of
+    /// attempts, so the flash is likely worn out.  Contains the offset of
+    /// the sector.
+    EraseWornOut { offset: u32 },
+
+    /// Flash read back via XIP after programming doesn't match the data
+    /// programmed.  Contains the offset of the first byte which differs.
+    VerifyFailed { offset: u32 },
+
+    /// An erase or program would write outside the writable regions of the
+    /// [`FlashLayout`].  Contains the offset of the first byte outside them.
+    OutOfBounds { offset: u32 },
+
+    /// A flash command's dummy cycles aren't a whole number of bytes.
+    /// Contains the number of cycles requested.
+    InvalidDummyCycles(u8),
+
+    /// An SSI clock divider is zero or odd, which the SSI doesn't support.
+    /// Contains the divider requested.
+    InvalidClockDivider(u8),
+}
+
+/// Converts an offset from the start of flash to its address in the XIP
+/// window.
+///
+/// The flash functions in this crate take offsets, so use this to get the
+/// address to read the flash at.  `offset` must be within the 16MB window.
+pub const fn flash_offset_to_addr(offset: u32) -> u32 {
+    XIP_BASE + offset
+}
+
+/// Converts an address in the XIP window to an offset from the start of
+/// flash, as the flash functions in this crate take.
+///
+/// Returns `None` if `addr` isn't in the 16MB window starting at
+/// [`XIP_BASE`].  The window's uncached aliases aren't accepted.
+pub const fn addr_to_flash_offset(addr: u32) -> Option<u32> {
+    if addr >= XIP_BASE && addr - XIP_BASE < XIP_WINDOW_SIZE as u32 {
+        Some(addr - XIP_BASE)
+    } else {
+        None
+    }
+}
+
+/// An address in flash, as an offset from the start of flash (not an
+/// absolute address in the XIP window)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FlashAddr(u32);
+
+impl FlashAddr {
+    /// Creates a flash address from an offset from the start of flash.
+    pub const fn new(offset: u32) -> Self {
+        Self(offset)
+    }
+
+    /// Returns the offset from the start of flash.
+    pub const fn offset(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for FlashAddr {
+    fn from(offset: u32) -> Self {
+        Self(offset)
+    }
+}
+
+/// The size of a region of flash, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FlashSize(u32);
+
+impl FlashSize {
+    /// Creates a flash size from a number of bytes.
+    pub const fn new(bytes: u32) -> Self {
+        Self(bytes)
+    }
+
+    /// Returns the size in bytes.
+    pub const fn bytes(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for FlashSize {
+    fn from(bytes: u32) -> Self {
+        Self(bytes)
+    }
+}
+
+/// The regions of flash which may be erased or programmed, as offsets from
+/// the start of flash, so a bug in, for example, an OTA update can't
+/// overwrite the bootloader.
+///
+/// [`FlashOps::with_layout`](crate::FlashOps::with_layout) makes its erase
+/// and program functions check every write against the layout.
+///
+/// ```rust
+/// use rp2040_rom::{FlashError, FlashLayout};
+///
+/// // The bootloader is in the first 64KB, so only the rest is writable
+/// const LAYOUT: FlashLayout = FlashLayout::new(&[0x1_0000..0x20_0000]);
+///
+/// assert_eq!(LAYOUT.check(0x1_0000, 4096), Ok(()));
+/// assert_eq!(LAYOUT.check(0, 4096), Err(FlashError::OutOfBounds { offset: 0 }));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FlashLayout {
+    writable: &'static [core::ops::Range<u32>],
+}
+
+impl FlashLayout {
+    /// Creates a layout from the writable regions.  Regions may be in any
+    /// order, and a write may span adjacent regions.
+    pub const fn new(writable: &'static [core::ops::Range<u32>]) -> Self {
+        Self { writable }
+    }
+
+    /// Returns the writable regions.
+    pub const fn writable(&self) -> &'static [core::ops::Range<u32>] {
+        self.writable
+    }
+
+    /// Checks `len` bytes at `offset` are all within the writable regions.
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::OutOfBounds`] for the first byte outside them.
+    pub fn check(&self, offset: u32, len: usize) -> Result<(), FlashError> {
+        let end = offset as u64 + len as u64;
+        let mut pos = offset;
+        while (pos as u64) < end {
+            match self.writable.iter().find(|region| region.contains(&pos)) {
+                Some(region) => pos = region.end,
+                None => return Err(FlashError::OutOfBounds { offset: pos }),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A word aligned buffer of `N` bytes, for staging data to program to flash.
+///
+/// `N` must be a multiple of [`FLASH_PAGE_SIZE`], which is checked at
+/// compile time.  The word alignment allows `memcpy44` and `memset4` to be
+/// used to fill it.  See [`PageBuffer`] and [`SectorBuffer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C, align(4))]
+pub struct FlashBuffer<const N: usize>([u8; N]);
+
+/// A buffer holding a single flash page
+pub type PageBuffer = FlashBuffer<{ FLASH_PAGE_SIZE as usize }>;
+
+/// A buffer holding a whole flash sector
+pub type SectorBuffer = FlashBuffer<{ FLASH_SECTOR_SIZE as usize }>;
+
+impl<const N: usize> FlashBuffer<N> {
+    /// Creates a buffer filled with `0xFF`, the value of erased flash.
+    pub const fn new() -> Self {
+        const { assert!(N > 0 && N.is_multiple_of(FLASH_PAGE_SIZE as usize)) };
+        Self([0xFF; N])
+    }
+
+    /// Creates a buffer from `data`, padding with `0xFF` if `data` is
+    /// shorter than the buffer.  Bytes beyond the length of the buffer are
+    /// ignored.
+    pub fn from_slice(data: &[u8]) -> Self {
+        let mut buf = Self::new();
+        buf.fill_from(data);
+        buf
+    }
+
+    /// Fills the buffer from `data`, padding with `0xFF` if `data` is
+    /// shorter than the buffer.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes copied from `data`.
+    pub fn fill_from(&mut self, data: &[u8]) -> usize {
+        let len = data.len().min(N);
+        self.0[..len].copy_from_slice(&data[..len]);
+        self.0[len..].fill(0xFF);
+        len
+    }
+
+    /// Returns the contents of the buffer.
+    pub const fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    /// Returns the contents of the buffer, mutably.
+    pub fn as_mut_bytes(&mut self) -> &mut [u8; N] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> Default for FlashBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The flash functions, on their own.
+///
+/// A zero-sized namespace containing only the flash functions of [`ROM`],
+/// which it calls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RomFlash;
+
+/// Public flash functions
+impl ROM {
+    /// Connects the SSI to the QSPI pads, restoring the default QSPI pad
+    /// configuration.
+    ///
+    /// # Safety
+    ///
+    /// Must be called with XIP disabled, or from code not running from
+    /// flash.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub unsafe fn connect_internal_flash() {
+        type RomConnectInternalFlashFn = unsafe extern "C" fn();
+
+        let func_ptr = Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH);
+        let func: RomConnectInternalFlashFn = core::mem::transmute(func_ptr);
+        func();
+    }
+
+    /// Takes the flash out of XIP mode, and initializes the SSI for serial
+    /// commands.
+    ///
+    /// # Safety
+    ///
+    /// Flash is not readable or executable after this call, until XIP is
+    /// re-entered.  Must not be called from code running from flash.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub unsafe fn flash_exit_xip() {
+        type RomFlashExitXipFn = unsafe extern "C" fn();
+
+        let func_ptr = Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP);
+        let func: RomFlashExitXipFn = core::mem::transmute(func_ptr);
+        func();
+    }
+
+    /// Flushes and re-enables the XIP cache.  Required after flash has been
+    /// modified, so stale cached contents aren't returned.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called from code running from flash while XIP is
+    /// disabled.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub unsafe fn flash_flush_cache() {
+        type RomFlashFlushCacheFn = unsafe extern "C" fn();
+
+        let func_ptr = Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE);
+        let func: RomFlashFlushCacheFn = core::mem::transmute(func_ptr);
+        func();
+    }
+
+    /// Configures the SSI to generate a standard 03h serial read command for
+    /// each XIP access.  This is a slow XIP mode, but works with any flash
+    /// device.
+    ///
+    /// # Safety
+    ///
+    /// Must not be called from code running from flash.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub unsafe fn flash_enter_cmd_xip() {
+        type RomFlashEnterCmdXipFn = unsafe extern "C" fn();
+
+        let func_ptr = Self::rom_func_lookup(rom_codes::FLASH_ENTER_CMD_XIP);
+        let func: RomFlashEnterCmdXipFn = core::mem::transmute(func_ptr);
+        func();
+    }
+
+    /// Resolves the flash functions, connects the flash and exits XIP, as
+    /// [`FlashAccess::begin`], returning a [`FlashAccess`] guard which
+    /// erases and programs flash, and restores XIP when dropped.
+    ///
+    /// The raw flash functions, such as [`ROM::flash_range_erase`], can't
+    /// be used in its place, as each looks its ROM function up from flash.
+    ///
+    /// # Returns
+    ///
+    /// The guard, or [`RomError::FunctionNotFound`] for the first flash
+    /// function which isn't present in the ROM, in which case XIP is left
+    /// enabled.
+    ///
+    /// # Safety
+    ///
+    /// As for [`FlashAccess::begin`].  Until the guard is dropped, nothing
+    /// may execute from flash, so a panic, whose handler runs from flash,
+    /// faults rather than restoring XIP.
+    pub unsafe fn begin_flash_ops() -> Result<FlashAccess, RomError> {
+        FlashAccess::begin()
+    }
+
+    /// Erases a range of flash.
+    ///
+    /// Uses `block_cmd` to erase `block_size` blocks where possible, falling
+    /// back to 4KB sector erases (20h) elsewhere.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Offset from the start of flash, a multiple of 4096
+    /// * `count` - Number of bytes to erase, a multiple of 4096
+    /// * `block_size` - Size of the block erased by `block_cmd`, e.g.
+    ///   [`FLASH_BLOCK_SIZE`]
+    /// * `block_cmd` - Block erase command, e.g. [`FLASH_BLOCK_ERASE_CMD`]
+    ///
+    /// # Safety
+    ///
+    /// Must be called with XIP disabled (see [`ROM::flash_exit_xip`]), from
+    /// code not running from flash.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub unsafe fn flash_range_erase(addr: u32, count: usize, block_size: u32, block_cmd: u8) {
+        type RomFlashRangeEraseFn =
+            unsafe extern "C" fn(addr: u32, count: usize, block_size: u32, block_cmd: u8);
+
+        let func_ptr = Self::rom_func_lookup(rom_codes::FLASH_RANGE_ERASE);
+        let func: RomFlashRangeEraseFn = core::mem::transmute(func_ptr);
+        func(addr, count, block_size, block_cmd);
+    }
+
+    /// Programs a range of flash, which must previously have been erased.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Offset from the start of flash, a multiple of 256
+    /// * `data` - Data to program, which must not be in flash
+    /// * `count` - Number of bytes to program, a multiple of 256
+    ///
+    /// # Safety
+    ///
+    /// Must be called with XIP disabled (see [`ROM::flash_exit_xip`]), from
+    /// code not running from flash.  `data` must be valid for `count` bytes.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub unsafe fn flash_range_program(addr: u32, data: *const u8, count: usize) {
+        type RomFlashRangeProgramFn =
+            unsafe extern "C" fn(addr: u32, data: *const u8, count: usize);
+
+        let func_ptr = Self::rom_func_lookup(rom_codes::FLASH_RANGE_PROGRAM);
+        let func: RomFlashRangeProgramFn = core::mem::transmute(func_ptr);
+        func(addr, data, count);
+    }
+
+    /// Programs `data` to flash at `offset`, as
+    /// [`ROM::flash_range_program`], but taking a slice, so the pointer and
+    /// length can't disagree, and checking the alignment first.
+    ///
+    /// # Parameters
+    ///
+    /// * `offset` - Offset from the start of flash, a multiple of 256
+    /// * `data` - Data to program, a multiple of 256 bytes, which must not be
+    ///   in flash
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`], without touching the flash, if `offset` or
+    /// the length of `data` isn't a multiple of 256.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_range_program`].
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub unsafe fn flash_program(offset: u32, data: &[u8]) -> Result<(), FlashError> {
+        if !offset.is_multiple_of(FLASH_PAGE_SIZE)
+            || !data.len().is_multiple_of(FLASH_PAGE_SIZE as usize)
+        {
+            return Err(FlashError::Unaligned);
+        }
+
+        Self::flash_range_program(offset, data.as_ptr(), data.len());
+        Ok(())
+    }
+
+    /// Erases a range of flash, performing the complete sequence of exiting
+    /// XIP, erasing, flushing the cache and re-entering XIP from RAM.
+    ///
+    /// XIP is re-entered in the slow 03h command mode, see
+    /// [`ROM::flash_enter_cmd_xip`].
+    ///
+    /// This function is placed in the `.ram_text` section, which must be
+    /// located in RAM - see the
+    /// [crate documentation](crate#running-flash-operations-from-ram).
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Offset from the start of flash, a multiple of 4096
+    /// * `count` - Number of bytes to erase, a multiple of 4096
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.  The `.ram_text` section
+    /// must have been copied to RAM.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".ram_text"
+    )]
+    pub unsafe fn flash_erase_ram(addr: u32, count: usize) {
+        debug_assert_interrupts_disabled();
+        debug_check!(
+            addr.is_multiple_of(FLASH_SECTOR_SIZE)
+                && count.is_multiple_of(FLASH_SECTOR_SIZE as usize),
+            "flash erase must be sector aligned"
+        );
+
+        type RomFlashFn = unsafe extern "C" fn();
+        type RomFlashRangeEraseFn = unsafe extern "C" fn(u32, usize, u32, u8);
+
+        // Look up all the ROM functions we need before exiting XIP, as the
+        // lookup code lives in flash
+        let connect_internal_flash: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH));
+        let flash_exit_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP));
+        let flash_range_erase: RomFlashRangeEraseFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_RANGE_ERASE));
+        let flash_flush_cache: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE));
+        let flash_enter_cmd_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_ENTER_CMD_XIP));
+
+        connect_internal_flash();
+        flash_exit_xip();
+        flash_range_erase(addr, count, FLASH_BLOCK_SIZE, FLASH_BLOCK_ERASE_CMD);
+        flash_flush_cache();
+        cache_flush_barrier();
+        flash_enter_cmd_xip();
+    }
+
+    /// Programs a range of flash, performing the complete sequence of exiting
+    /// XIP, programming, flushing the cache and re-entering XIP from RAM.
+    ///
+    /// XIP is re-entered in the slow 03h command mode, see
+    /// [`ROM::flash_enter_cmd_xip`].
+    ///
+    /// This function is placed in the `.ram_text` section, which must be
+    /// located in RAM - see the
+    /// [crate documentation](crate#running-flash-operations-from-ram).
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Offset from the start of flash, a multiple of 256
+    /// * `data` - Data to program, a multiple of 256 bytes, which must not be
+    ///   in flash
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.  The `.ram_text` section
+    /// must have been copied to RAM.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".ram_text"
+    )]
+    pub unsafe fn flash_program_ram(addr: u32, data: &[u8]) {
+        debug_assert_interrupts_disabled();
+        debug_check!(
+            addr.is_multiple_of(FLASH_PAGE_SIZE)
+                && data.len().is_multiple_of(FLASH_PAGE_SIZE as usize),
+            "flash program must be page aligned"
+        );
+
+        type RomFlashFn = unsafe extern "C" fn();
+        type RomFlashRangeProgramFn = unsafe extern "C" fn(u32, *const u8, usize);
+
+        // Look up all the ROM functions we need before exiting XIP, as the
+        // lookup code lives in flash
+        let connect_internal_flash: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH));
+        let flash_exit_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP));
+        let flash_range_program: RomFlashRangeProgramFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_RANGE_PROGRAM));
+        let flash_flush_cache: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE));
+        let flash_enter_cmd_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_ENTER_CMD_XIP));
+
+        connect_internal_flash();
+        flash_exit_xip();
+        flash_range_program(addr, data.as_ptr(), data.len());
+        flash_flush_cache();
+        cache_flush_barrier();
+        flash_enter_cmd_xip();
+    }
+
+    /// Programs flash from `data` of any length, as
+    /// [`ROM::flash_program_ram`], padding the last page with `0xFF`.
+    ///
+    /// The ROM programs whole pages, so padding with the erased value leaves
+    /// the rest of the last page unchanged, rather than programming it with
+    /// whatever follows `data` in memory.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Offset from the start of flash, a multiple of 256
+    /// * `data` - Data to program, which must not be in flash
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `addr` isn't page aligned.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_ram`].
+    pub unsafe fn flash_write_padded(addr: u32, data: &[u8]) -> Result<(), FlashError> {
+        RomFlashBackend.write_padded(addr, data)
+    }
+
+    /// Programs flash from `data` of any length, as
+    /// [`ROM::flash_write_padded`], then reads it back via XIP, comparing
+    /// every byte with `data`.
+    ///
+    /// Use this where silently corrupted flash can't be tolerated, such as
+    /// when writing firmware from an OTA bootloader.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Offset from the start of flash, a multiple of 256
+    /// * `data` - Data to program, which must not be in flash
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `addr` isn't page aligned, or
+    /// [`FlashError::VerifyFailed`] for the first byte which differs.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_ram`].
+    pub unsafe fn flash_write_verified(addr: u32, data: &[u8]) -> Result<(), FlashError> {
+        Self::flash_write(addr, data, true)
+    }
+
+    /// Programs flash from `data` of any length, as
+    /// [`ROM::flash_write_padded`], and if `verify` is set, reads it back as
+    /// [`ROM::flash_write_verified`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_ram`].
+    pub unsafe fn flash_write(addr: u32, data: &[u8], verify: bool) -> Result<(), FlashError> {
+        RomFlashBackend.write(addr, data, verify)
+    }
+
+    /// Programs flash from `data` of any length, as
+    /// [`ROM::flash_write_padded`], a sector at a time, calling
+    /// `on_progress` after each sector, for example to drive a progress bar
+    /// during an OTA update.
+    ///
+    /// `on_progress` receives the number of bytes written so far, and the
+    /// total, `data.len()`.  It is called between sectors, once XIP has been
+    /// re-entered, so may run from flash.  But it runs in the caller's
+    /// context, so mustn't enable interrupts, let the other core execute
+    /// from flash, or itself modify flash.
+    ///
+    /// # Parameters
+    ///
+    /// * `offset` - Offset from the start of flash, a multiple of 256
+    /// * `data` - Data to program, which must not be in flash
+    /// * `on_progress` - Called with `(bytes_written, total_bytes)`
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `offset` isn't page aligned, in which
+    /// case nothing is programmed.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_ram`].
+    pub unsafe fn flash_program_with_progress(
+        offset: u32,
+        data: &[u8],
+        on_progress: impl FnMut(usize, usize),
+    ) -> Result<(), FlashError> {
+        RomFlashBackend.program_with_progress(offset, data, on_progress)
+    }
+
+    /// Programs a range of flash, as [`ROM::flash_program_ram`], then
+    /// verifies it by comparing the CRC-32 of the flash contents, read via
+    /// XIP, with the CRC-32 of `data`.
+    ///
+    /// Slower than a byte by byte comparison, as [`ROM::crc32`] works a bit
+    /// at a time, over both buffers.  But a mismatch reports both CRC-32s,
+    /// so one can be checked against a CRC-32 stored with the image, such
+    /// as an OTA update's.
+    ///
+    /// # Parameters
+    ///
+    /// * `offset` - Offset from the start of flash, a multiple of 256
+    /// * `data` - Data to program, a multiple of 256 bytes, which must not be
+    ///   in flash
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `offset` or the length of `data` isn't
+    /// page aligned, in which case nothing is programmed, or
+    /// [`FlashError::CrcMismatch`] if the CRCs don't match.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_ram`].
+    pub unsafe fn flash_program_verify_crc(offset: u32, data: &[u8]) -> Result<(), FlashError> {
+        RomFlashBackend.program_verify_crc(offset, data)
+    }
+
+    /// Erases a range of flash, as [`ROM::flash_erase_ram`], verifying each
+    /// sector reads back as all `0xFF`, and retrying any which doesn't.
+    ///
+    /// On aging flash an erase can leave stray 0 bits.  Each retry erases
+    /// only the failing sector, to avoid further wear.
+    ///
+    /// # Parameters
+    ///
+    /// * `offset` - Offset from the start of flash, a multiple of 4096
+    /// * `len` - Number of bytes to erase, a multiple of 4096
+    /// * `max_attempts` - Maximum number of times to erase each sector.  0 is
+    ///   treated as 1.
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `offset` or `len` isn't sector aligned, or
+    /// [`FlashError::EraseWornOut`] for the first sector which couldn't be
+    /// erased.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_erase_ram`].
+    pub unsafe fn flash_erase_retry(
+        offset: u32,
+        len: usize,
+        max_attempts: u8,
+    ) -> Result<(), FlashError> {
+        RomFlashBackend.erase_retry(offset, len, max_attempts)
+    }
+
+    /// Returns a slice over the first `len` bytes of flash, as mapped into
+    /// the XIP window at `0x1000_0000`.
+    ///
+    /// Useful for reading data, such as configuration or resources, placed
+    /// in flash at a known offset.  `len` is clamped to the 16MB XIP window,
+    /// but it is the caller's responsibility not to exceed the size of the
+    /// flash device.
+    #[cfg_attr(
+        not(feature = "rp2350"),
+        doc = "On the RP2040, detect it with [`ROM::flash_size`], which reads the flash's JEDEC ID."
+    )]
+    ///
+    /// # Safety
+    ///
+    /// XIP must be enabled whenever the slice is read, and the flash must
+    /// not be modified while the slice is in use.
+    pub unsafe fn flash_contents_len(len: usize) -> &'static [u8] {
+        let len = if len > XIP_WINDOW_SIZE {
+            XIP_WINDOW_SIZE
+        } else {
+            len
+        };
+        core::slice::from_raw_parts(XIP_BASE as usize as *const u8, len)
+    }
+
+    /// Checks whether a boot2 (second stage bootloader) image has a valid
+    /// checksum, as the ROM does before running boot2 from the start of
+    /// flash.
+    ///
+    /// A boot2 image is 256 bytes: 252 bytes of code, followed by a
+    /// little-endian CRC-32 of those 252 bytes.  The CRC is the MPEG-2
+    /// variant - polynomial 0x04C11DB7, initial value 0xFFFFFFFF, with no
+    /// bit reflection and no final XOR.  The ROM doesn't export its own
+    /// check, so this recalculates it.
+    ///
+    /// Useful for bootloaders, to verify a newly written boot2 before
+    /// relying on it, including one stored away from the start of flash,
+    /// such as a staged update - pass its address in the XIP window.  The
+    /// CRC always covers exactly 252 bytes, so there's no length to give.
+    ///
+    /// # Parameters
+    ///
+    /// * `flash_ptr` - The boot2 image, for example `0x1000_0000` for the
+    ///   boot2 in flash, or a copy in RAM
+    ///
+    /// # Returns
+    ///
+    /// true if the stored CRC matches, false otherwise.
+    ///
+    /// # Safety
+    ///
+    /// `flash_ptr` must be valid for reads of 256 bytes.  If it is in
+    /// flash, XIP must be enabled.
+    #[cfg(not(feature = "rp2350"))]
+    pub unsafe fn validate_boot2(flash_ptr: *const u8) -> bool {
+        const BOOT2_CODE_SIZE: usize = 252;
+
+        let image = core::slice::from_raw_parts(flash_ptr, BOOT2_CODE_SIZE + 4);
+        let (code, crc) = image.split_at(BOOT2_CODE_SIZE);
+        boot2_crc32(code) == u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]])
+    }
+}
+
+// Barriers after flushing the XIP cache, before re-entering XIP.  The DSB
+// waits for the flush to complete, and the ISB discards any instructions
+// fetched before it, so stale cached code can't run once XIP is back.
+#[inline(always)]
+pub(crate) fn cache_flush_barrier() {
+    // Safety: the barriers have no effect other than ordering
+    #[cfg(target_arch = "arm")]
+    unsafe {
+        core::arch::asm!("dsb sy", "isb sy", options(nostack, preserves_flags));
+    }
+}
+
+// Panics if interrupts are enabled, in debug builds, so calling a flash
+// sequence from interrupt enabled code fails clearly, rather than an
+// interrupt handler faulting once XIP is disabled.  Must be called before XIP
+// is disabled, as the panic handler is in flash.
+#[inline(always)]
+pub(crate) fn debug_assert_interrupts_disabled() {
+    #[cfg(all(debug_assertions, target_arch = "arm"))]
+    {
+        let primask: u32;
+        // Safety: reading PRIMASK has no side effects
+        unsafe {
+            core::arch::asm!(
+                "mrs {}, PRIMASK",
+                out(reg) primask,
+                options(nomem, nostack, preserves_flags)
+            );
+        }
+        debug_check!(
+            primask & 1 == 1,
+            "flash operations require interrupts disabled"
+        );
+    }
+}
+
+// Whether any of the `len` bytes at `ptr` are in one of the XIP window's
+// aliases of flash, excluding the RP2350's XIP SRAM at its top.  Inlined, as
+// it is called with XIP disabled.
+#[inline(always)]
+pub(crate) fn in_xip_flash(ptr: *const u8, len: usize) -> bool {
+    let end = if XIP_SRAM_BASE < XIP_ALIASES_END {
+        XIP_SRAM_BASE
+    } else {
+        XIP_ALIASES_END
+    };
+    let start = ptr as usize;
+    len != 0 && start < end as usize && start.saturating_add(len) > XIP_BASE as usize
+}
+
+// Calculates the CRC-32/MPEG-2 of `data`, as used by the ROM to check boot2,
+// bit by bit
+#[cfg(not(feature = "rp2350"))]
+fn boot2_crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= (byte as u32) << 24;
+        for _ in 0..8 {
+            let mask = (crc >> 31).wrapping_neg();
+            crc = (crc << 1) ^ (0x04C1_1DB7 & mask);
+        }
+    }
+    crc
+}
+
+/// Public flash functions
+impl RomFlash {
+    /// See [`ROM::connect_internal_flash`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::connect_internal_flash`].
+    #[inline]
+    pub unsafe fn connect_internal_flash() {
+        ROM::connect_internal_flash()
+    }
+
+    /// See [`ROM::flash_exit_xip`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_exit_xip`].
+    #[inline]
+    pub unsafe fn flash_exit_xip() {
+        ROM::flash_exit_xip()
+    }
+
+    /// See [`ROM::flash_flush_cache`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_flush_cache`].
+    #[inline]
+    pub unsafe fn flash_flush_cache() {
+        ROM::flash_flush_cache()
+    }
+
+    /// See [`ROM::flash_enter_cmd_xip`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_enter_cmd_xip`].
+    #[inline]
+    pub unsafe fn flash_enter_cmd_xip() {
+        ROM::flash_enter_cmd_xip()
+    }
+
+    /// See [`ROM::begin_flash_ops`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::begin_flash_ops`].
+    #[inline]
+    pub unsafe fn begin_flash_ops() -> Result<FlashAccess, RomError> {
+        ROM::begin_flash_ops()
+    }
+
+    /// See [`ROM::flash_range_erase`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_range_erase`].
+    #[inline]
+    pub unsafe fn flash_range_erase(addr: u32, count: usize, block_size: u32, block_cmd: u8) {
+        ROM::flash_range_erase(addr, count, block_size, block_cmd)
+    }
+
+    /// See [`ROM::flash_range_program`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_range_program`].
+    #[inline]
+    pub unsafe fn flash_range_program(addr: u32, data: *const u8, count: usize) {
+        ROM::flash_range_program(addr, data, count)
+    }
+
+    /// See [`ROM::flash_program`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program`].
+    #[inline]
+    pub unsafe fn flash_program(offset: u32, data: &[u8]) -> Result<(), FlashError> {
+        ROM::flash_program(offset, data)
+    }
+
+    /// See [`ROM::flash_erase_ram`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_erase_ram`].
+    #[inline]
+    pub unsafe fn flash_erase_ram(addr: u32, count: usize) {
+        ROM::flash_erase_ram(addr, count)
+    }
+
+    /// See [`ROM::flash_program_ram`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_ram`].
+    #[inline]
+    pub unsafe fn flash_program_ram(addr: u32, data: &[u8]) {
+        ROM::flash_program_ram(addr, data)
+    }
+
+    /// See [`ROM::validate_boot2`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::validate_boot2`].
+    #[cfg(not(feature = "rp2350"))]
+    #[inline]
+    pub unsafe fn validate_boot2(flash_ptr: *const u8) -> bool {
+        ROM::validate_boot2(flash_ptr)
+    }
+
+    /// See [`ROM::flash_contents_len`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_contents_len`].
+    #[inline]
+    pub unsafe fn flash_contents_len(len: usize) -> &'static [u8] {
+        ROM::flash_contents_len(len)
+    }
+
+    /// See [`ROM::flash_program_with_progress`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_with_progress`].
+    #[inline]
+    pub unsafe fn flash_program_with_progress(
+        offset: u32,
+        data: &[u8],
+        on_progress: impl FnMut(usize, usize),
+    ) -> Result<(), FlashError> {
+        ROM::flash_program_with_progress(offset, data, on_progress)
+    }
+
+    /// See [`ROM::flash_program_verify_crc`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_verify_crc`].
+    #[inline]
+    pub unsafe fn flash_program_verify_crc(offset: u32, data: &[u8]) -> Result<(), FlashError> {
+        ROM::flash_program_verify_crc(offset, data)
+    }
+
+    /// See [`ROM::flash_write_padded`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_write_padded`].
+    #[inline]
+    pub unsafe fn flash_write_padded(addr: u32, data: &[u8]) -> Result<(), FlashError> {
+        ROM::flash_write_padded(addr, data)
+    }
+
+    /// See [`ROM::flash_erase_retry`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_erase_retry`].
+    #[inline]
+    pub unsafe fn flash_erase_retry(
+        offset: u32,
+        len: usize,
+        max_attempts: u8,
+    ) -> Result<(), FlashError> {
+        ROM::flash_erase_retry(offset, len, max_attempts)
+    }
+
+    /// See [`ROM::flash_write_verified`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_write_verified`].
+    #[inline]
+    pub unsafe fn flash_write_verified(addr: u32, data: &[u8]) -> Result<(), FlashError> {
+        ROM::flash_write_verified(addr, data)
+    }
+
+    /// See [`ROM::flash_write`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_write`].
+    #[inline]
+    pub unsafe fn flash_write(addr: u32, data: &[u8], verify: bool) -> Result<(), FlashError> {
+        ROM::flash_write(addr, data, verify)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flash_program_rejects_unaligned() {
+        // Rejected before the ROM is touched
+        unsafe {
+            assert_eq!(
+                ROM::flash_program(0x80, &[0; 256]),
+                Err(FlashError::Unaligned)
+            );
+            assert_eq!(
+                ROM::flash_program(0x100, &[0; 100]),
+                Err(FlashError::Unaligned)
+            );
+        }
+    }
+
+    #[test]
+    fn flash_layout_checks_regions() {
+        const LAYOUT: FlashLayout = FlashLayout::new(&[0x3000..0x4000, 0x1000..0x3000]);
+
+        assert_eq!(LAYOUT.check(0x1000, 0x3000), Ok(()));
+        assert_eq!(LAYOUT.check(0x2000, 0), Ok(()));
+        assert_eq!(
+            LAYOUT.check(0x0f00, 0x200),
+            Err(FlashError::OutOfBounds { offset: 0x0f00 })
+        );
+        assert_eq!(
+            LAYOUT.check(0x3f00, 0x200),
+            Err(FlashError::OutOfBounds { offset: 0x4000 })
+        );
+        assert_eq!(
+            FlashLayout::new(&[]).check(0, 1),
+            Err(FlashError::OutOfBounds { offset: 0 })
+        );
+    }
+
+    #[test]
+    fn flash_offset_conversions() {
+        assert_eq!(flash_offset_to_addr(0x1000), 0x1000_1000);
+        assert_eq!(addr_to_flash_offset(0x1000_1000), Some(0x1000));
+        assert_eq!(addr_to_flash_offset(0x0fff_ffff), None);
+        assert_eq!(addr_to_flash_offset(0x1100_0000), None);
+    }
+
+    #[test]
+    fn in_xip_flash_covers_aliases() {
+        let ptr = |addr: usize| addr as *const u8;
+        assert!(in_xip_flash(ptr(0x1000_0000), 1));
+        assert!(in_xip_flash(ptr(0x0fff_ff00), 0x101));
+        assert!(in_xip_flash(ptr(0x1300_0000), 256));
+        assert!(!in_xip_flash(ptr(0x0fff_ff00), 0x100));
+        assert!(!in_xip_flash(ptr(0x1000_0000), 0));
+        assert!(!in_xip_flash(ptr(0x2000_0000), 256));
+        assert!(!in_xip_flash(ptr(XIP_SRAM_BASE as usize), 256));
+    }
+
+    #[test]
+    fn flash_buffer_pads_with_erased_value() {
+        let page = PageBuffer::from_slice(&[1, 2, 3]);
+        assert_eq!(&page.as_bytes()[..4], &[1, 2, 3, 0xFF]);
+        assert!(page.as_bytes()[3..].iter().all(|&b| b == 0xFF));
+    }
+
+    #[test]
+    fn flash_buffer_truncates_and_is_aligned() {
+        let mut sector = SectorBuffer::new();
+        assert_eq!(sector.fill_from(&[0; 5000]), 4096);
+        assert_eq!(sector.as_bytes().as_ptr() as usize % 4, 0);
+    }
+
+    #[cfg(not(feature = "rp2350"))]
+    #[test]
+    fn boot2_crc_check_value_and_validation() {
+        assert_eq!(boot2_crc32(b"123456789"), 0x0376_E6E7);
+
+        let mut image = [0xA5u8; 256];
+        let crc = boot2_crc32(&image[..252]);
+        image[252..].copy_from_slice(&crc.to_le_bytes());
+        assert!(unsafe { ROM::validate_boot2(image.as_ptr()) });
+
+        image[0] ^= 1;
+        assert!(!unsafe { ROM::validate_boot2(image.as_ptr()) });
+    }
+}
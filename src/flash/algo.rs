@@ -0,0 +1,165 @@
+//! Entry points for a CMSIS-Pack flash algorithm, as used by `probe-rs`.
+//!
+//! A flash algorithm is a small image which a debug probe loads into RAM,
+//! and calls to erase and program flash.  Building one from this crate
+//! needs:
+//!
+//! * The whole image, code and data, linked to RAM.  The ROM functions exit
+//!   XIP, so nothing may be fetched from flash while they run.  No vector
+//!   table or runtime is needed, as the probe sets up the stack and calls
+//!   the entry points directly.
+//! * The entry points kept by the linker, as nothing in the image calls
+//!   them, for example with `EXTERN(FlashInit FlashUninit EraseSector
+//!   ProgramPage)` in the linker script.
+//! * A `FlashDevice` description, and the `PrgCode` and `PrgData` sections,
+//!   as the algorithm template for your tool expects.
+//!
+//! As the ABI requires, each entry point returns 0 on success, or 1 on
+//! failure, and takes absolute addresses, in the XIP window at
+//! `0x1000_0000`.  The probe starts the algorithm with interrupts disabled,
+//! and the other core halted.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use super::{
+    addr_to_flash_offset, FLASH_BLOCK_ERASE_CMD, FLASH_BLOCK_SIZE, FLASH_PAGE_SIZE,
+    FLASH_SECTOR_SIZE, XIP_WINDOW_SIZE,
+};
+use crate::ROM;
+
+/// Returned by the entry points on success
+const ALGO_OK: i32 = 0;
+
+/// Returned by the entry points on failure
+const ALGO_ERROR: i32 = 1;
+
+/// Initializes the flash for erase or program, by exiting XIP.
+///
+/// # Parameters
+///
+/// * `adr` - The base address of the flash, unused
+/// * `clk` - The clock frequency, unused
+/// * `fnc` - The operation to follow: 1 for erase, 2 for program or 3 for
+///   verify, unused
+///
+/// # Safety
+///
+/// Must only be called by the debug probe, as described in the module
+/// documentation.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub unsafe extern "C" fn FlashInit(_adr: u32, _clk: u32, _fnc: u32) -> i32 {
+    ROM::connect_internal_flash();
+    ROM::flash_exit_xip();
+    ALGO_OK
+}
+
+/// Restores XIP, in the slow 03h command mode, after erase or program.
+///
+/// # Parameters
+///
+/// * `fnc` - The operation which preceded this, unused
+///
+/// # Safety
+///
+/// As for [`FlashInit`].
+#[allow(non_snake_case)]
+#[no_mangle]
+pub unsafe extern "C" fn FlashUninit(_fnc: u32) -> i32 {
+    ROM::flash_flush_cache();
+    ROM::flash_enter_cmd_xip();
+    ALGO_OK
+}
+
+/// Erases the 4096 byte sector at `adr`.
+///
+/// # Safety
+///
+/// As for [`FlashInit`], and only between [`FlashInit`] and
+/// [`FlashUninit`].
+#[allow(non_snake_case)]
+#[no_mangle]
+pub unsafe extern "C" fn EraseSector(adr: u32) -> i32 {
+    let Some(offset) = flash_offset(adr, FLASH_SECTOR_SIZE) else {
+        return ALGO_ERROR;
+    };
+    if !offset.is_multiple_of(FLASH_SECTOR_SIZE) {
+        return ALGO_ERROR;
+    }
+
+    ROM::flash_range_erase(
+        offset,
+        FLASH_SECTOR_SIZE as usize,
+        FLASH_BLOCK_SIZE,
+        FLASH_BLOCK_ERASE_CMD,
+    );
+    ALGO_OK
+}
+
+/// Programs `sz` bytes from `buf` at `adr`, normally a single 256 byte
+/// page.
+///
+/// Fails, programming nothing, unless `adr` is page aligned and `sz` is a
+/// non-zero multiple of 256, as the ROM doesn't check.
+///
+/// # Safety
+///
+/// As for [`EraseSector`].  `buf` must be valid for reads of `sz` bytes, in
+/// RAM.
+#[allow(non_snake_case)]
+#[no_mangle]
+pub unsafe extern "C" fn ProgramPage(adr: u32, sz: u32, buf: *const u8) -> i32 {
+    let Some(offset) = program_offset(adr, sz) else {
+        return ALGO_ERROR;
+    };
+
+    ROM::flash_range_program(offset, buf, sz as usize);
+    ALGO_OK
+}
+
+// Converts an absolute address, for `len` bytes, to an offset from the start
+// of flash, if it is within the XIP window
+fn flash_offset(adr: u32, len: u32) -> Option<u32> {
+    let offset = addr_to_flash_offset(adr)?;
+    if offset as usize + len as usize > XIP_WINDOW_SIZE {
+        return None;
+    }
+
+    Some(offset)
+}
+
+// Converts an absolute address to program `len` bytes at to an offset from
+// the start of flash, if it is within the XIP window, page aligned, and
+// `len` is a non-zero multiple of the page size
+fn program_offset(adr: u32, len: u32) -> Option<u32> {
+    let offset = flash_offset(adr, len)?;
+    if !offset.is_multiple_of(FLASH_PAGE_SIZE) || len == 0 || !len.is_multiple_of(FLASH_PAGE_SIZE) {
+        return None;
+    }
+
+    Some(offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flash_offset_within_xip_window() {
+        assert_eq!(flash_offset(0x1000_1000, 4096), Some(0x1000));
+        assert_eq!(flash_offset(0x0fff_ffff, 1), None);
+        assert_eq!(flash_offset(0x10ff_ff00, 256), Some(0xff_ff00));
+        assert_eq!(flash_offset(0x10ff_ff00, 512), None);
+    }
+
+    #[test]
+    fn program_offset_is_page_aligned() {
+        assert_eq!(program_offset(0x1000_0100, 256), Some(0x100));
+        assert_eq!(program_offset(0x1000_0180, 256), None);
+        assert_eq!(program_offset(0x1000_0100, 0), None);
+        assert_eq!(program_offset(0x1000_0100, 100), None);
+        assert_eq!(program_offset(0x10ff_ff00, 512), None);
+    }
+}
@@ -0,0 +1,331 @@
+//! The flash backend, which the higher level flash functions - padding,
+//! chunking, verifying and retrying - are written against.
+//!
+//! [`RomFlashBackend`] erases and programs with the ROM, and is what the
+//! `ROM` flash functions use.  It is zero-sized, so its calls compile to
+//! direct calls to [`ROM::flash_erase_ram`] and [`ROM::flash_program_ram`].
+//! With the `mock` feature, [`MockFlash`] keeps a flash image in memory
+//! instead, so code written against [`FlashBackend`], such as a firmware
+//! update state machine, can be unit tested on the host.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use super::{flash_offset_to_addr, FlashError, PageBuffer, FLASH_PAGE_SIZE, FLASH_SECTOR_SIZE};
+use crate::ROM;
+
+/// Erases, programs and reads flash, by offset from the start of flash.
+///
+/// Implement [`erase`](FlashBackend::erase),
+/// [`program`](FlashBackend::program) and [`read`](FlashBackend::read), and
+/// the remaining methods, which match the `ROM` flash functions of the same
+/// name, are provided.
+pub trait FlashBackend {
+    /// Erases `len` bytes of flash at `offset`, both multiples of 4096.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_erase_ram`], for [`RomFlashBackend`].
+    unsafe fn erase(&mut self, offset: u32, len: usize);
+
+    /// Programs `data`, a multiple of 256 bytes, at `offset`, a multiple of
+    /// 256.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_program_ram`], for [`RomFlashBackend`].
+    unsafe fn program(&mut self, offset: u32, data: &[u8]);
+
+    /// Returns the `len` bytes of flash at `offset`.
+    ///
+    /// # Safety
+    ///
+    /// For [`RomFlashBackend`], XIP must be enabled, and the flash must not
+    /// be modified while the slice is in use.
+    unsafe fn read(&self, offset: u32, len: usize) -> &[u8];
+
+    /// As [`ROM::flash_write_padded`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`FlashBackend::program`].
+    unsafe fn write_padded(&mut self, offset: u32, data: &[u8]) -> Result<(), FlashError> {
+        if !offset.is_multiple_of(FLASH_PAGE_SIZE) {
+            return Err(FlashError::Unaligned);
+        }
+
+        let whole = data.len() - data.len() % FLASH_PAGE_SIZE as usize;
+        let (pages, tail) = data.split_at(whole);
+        if !pages.is_empty() {
+            self.program(offset, pages);
+        }
+        if !tail.is_empty() {
+            let page = PageBuffer::from_slice(tail);
+            self.program(offset + whole as u32, page.as_bytes());
+        }
+
+        Ok(())
+    }
+
+    /// As [`ROM::flash_write`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`FlashBackend::program`] and [`FlashBackend::read`].
+    unsafe fn write(&mut self, offset: u32, data: &[u8], verify: bool) -> Result<(), FlashError> {
+        self.write_padded(offset, data)?;
+
+        if verify {
+            let programmed = self.read(offset, data.len());
+            if let Some(index) = programmed.iter().zip(data).position(|(a, b)| a != b) {
+                return Err(FlashError::VerifyFailed {
+                    offset: offset + index as u32,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// As [`ROM::flash_program_with_progress`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`FlashBackend::program`].
+    unsafe fn program_with_progress(
+        &mut self,
+        offset: u32,
+        data: &[u8],
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<(), FlashError>
+    where
+        Self: Sized,
+    {
+        if !offset.is_multiple_of(FLASH_PAGE_SIZE) {
+            return Err(FlashError::Unaligned);
+        }
+
+        let mut written = 0;
+        for sector in data.chunks(FLASH_SECTOR_SIZE as usize) {
+            self.write_padded(offset + written as u32, sector)?;
+            written += sector.len();
+            on_progress(written, data.len());
+        }
+
+        Ok(())
+    }
+
+    /// As [`ROM::flash_program_verify_crc`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`FlashBackend::program`] and [`FlashBackend::read`].
+    unsafe fn program_verify_crc(&mut self, offset: u32, data: &[u8]) -> Result<(), FlashError> {
+        if !offset.is_multiple_of(FLASH_PAGE_SIZE)
+            || !data.len().is_multiple_of(FLASH_PAGE_SIZE as usize)
+        {
+            return Err(FlashError::Unaligned);
+        }
+
+        self.program(offset, data);
+
+        let expected = ROM::crc32(data, 0);
+        let found = ROM::crc32(self.read(offset, data.len()), 0);
+        if found != expected {
+            return Err(FlashError::CrcMismatch { expected, found });
+        }
+
+        Ok(())
+    }
+
+    /// As [`ROM::flash_erase_retry`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`FlashBackend::erase`] and [`FlashBackend::read`].
+    unsafe fn erase_retry(
+        &mut self,
+        offset: u32,
+        len: usize,
+        max_attempts: u8,
+    ) -> Result<(), FlashError> {
+        let sector_size = FLASH_SECTOR_SIZE as usize;
+        if !offset.is_multiple_of(FLASH_SECTOR_SIZE) || !len.is_multiple_of(sector_size) {
+            return Err(FlashError::Unaligned);
+        }
+
+        self.erase(offset, len);
+
+        for sector in (offset..offset + len as u32).step_by(sector_size) {
+            let mut attempts = 1;
+            // The contents are read afresh on each pass, as the flash is
+            // modified in between
+            while !self.read(sector, sector_size).iter().all(|&b| b == 0xFF) {
+                if attempts >= max_attempts {
+                    return Err(FlashError::EraseWornOut { offset: sector });
+                }
+                self.erase(sector, sector_size);
+                attempts += 1;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The flash backend used by the `ROM` flash functions, which erases and
+/// programs with [`ROM::flash_erase_ram`] and [`ROM::flash_program_ram`],
+/// and reads via XIP.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RomFlashBackend;
+
+impl FlashBackend for RomFlashBackend {
+    #[inline(always)]
+    unsafe fn erase(&mut self, offset: u32, len: usize) {
+        ROM::flash_erase_ram(offset, len)
+    }
+
+    #[inline(always)]
+    unsafe fn program(&mut self, offset: u32, data: &[u8]) {
+        ROM::flash_program_ram(offset, data)
+    }
+
+    #[inline(always)]
+    unsafe fn read(&self, offset: u32, len: usize) -> &[u8] {
+        core::slice::from_raw_parts(flash_offset_to_addr(offset) as usize as *const u8, len)
+    }
+}
+
+/// An in-memory flash image of `N` bytes, for testing code written against
+/// [`FlashBackend`] on the host.
+///
+/// Behaves as NOR flash: erasing sets bytes to `0xFF`, and programming can
+/// only clear bits, so programming over data which wasn't erased shows up
+/// as a verify failure, as it would on hardware.  Its methods have no
+/// safety requirements, but panic if called with an unaligned or out of
+/// range offset or length.
+#[cfg(feature = "mock")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockFlash<const N: usize> {
+    image: [u8; N],
+}
+
+#[cfg(feature = "mock")]
+impl<const N: usize> MockFlash<N> {
+    /// Creates a fully erased flash image.
+    pub const fn new() -> Self {
+        Self { image: [0xFF; N] }
+    }
+
+    /// Returns the flash image.
+    pub const fn image(&self) -> &[u8; N] {
+        &self.image
+    }
+
+    /// Returns the flash image, mutably, for example to simulate corruption.
+    pub fn image_mut(&mut self) -> &mut [u8; N] {
+        &mut self.image
+    }
+}
+
+#[cfg(feature = "mock")]
+impl<const N: usize> Default for MockFlash<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "mock")]
+impl<const N: usize> FlashBackend for MockFlash<N> {
+    unsafe fn erase(&mut self, offset: u32, len: usize) {
+        assert!(
+            offset.is_multiple_of(FLASH_SECTOR_SIZE)
+                && len.is_multiple_of(FLASH_SECTOR_SIZE as usize),
+            "erase must be sector aligned"
+        );
+        self.image[offset as usize..offset as usize + len].fill(0xFF);
+    }
+
+    unsafe fn program(&mut self, offset: u32, data: &[u8]) {
+        assert!(
+            offset.is_multiple_of(FLASH_PAGE_SIZE)
+                && data.len().is_multiple_of(FLASH_PAGE_SIZE as usize),
+            "program must be page aligned"
+        );
+        let range = offset as usize..offset as usize + data.len();
+        for (byte, &value) in self.image[range].iter_mut().zip(data) {
+            *byte &= value;
+        }
+    }
+
+    unsafe fn read(&self, offset: u32, len: usize) -> &[u8] {
+        &self.image[offset as usize..offset as usize + len]
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+
+    const SECTOR: usize = FLASH_SECTOR_SIZE as usize;
+
+    #[test]
+    fn write_pads_and_verifies() {
+        let mut flash = MockFlash::<{ 2 * SECTOR }>::new();
+        unsafe {
+            assert_eq!(flash.write(0x100, &[1, 2, 3], true), Ok(()));
+            assert_eq!(flash.write(0x101, &[1], false), Err(FlashError::Unaligned));
+
+            // Programming over programmed data can only clear bits
+            assert_eq!(
+                flash.write(0x100, &[1, 6], true),
+                Err(FlashError::VerifyFailed { offset: 0x101 })
+            );
+        }
+        assert_eq!(&flash.image()[0x100..0x104], &[1, 2, 3, 0xFF]);
+    }
+
+    #[test]
+    fn program_with_progress_chunks_by_sector() {
+        let mut flash = MockFlash::<{ 2 * SECTOR }>::new();
+        let data = [0x5A; SECTOR + 10];
+        let mut calls = [0; 2];
+        let mut count = 0;
+        let result = unsafe {
+            flash.program_with_progress(0, &data, |written, total| {
+                assert_eq!(total, data.len());
+                calls[count] = written;
+                count += 1;
+            })
+        };
+        assert_eq!(result, Ok(()));
+        assert_eq!(calls, [SECTOR, SECTOR + 10]);
+        assert_eq!(flash.image()[SECTOR + 10], 0xFF);
+    }
+
+    #[test]
+    fn erase_retry_and_crc_verify() {
+        let mut flash = MockFlash::<{ 2 * SECTOR }>::new();
+        flash.image_mut().fill(0);
+        unsafe {
+            assert_eq!(flash.erase_retry(0, 2 * SECTOR, 1), Ok(()));
+            assert!(flash.image().iter().all(|&b| b == 0xFF));
+            assert_eq!(flash.erase_retry(1, SECTOR, 1), Err(FlashError::Unaligned));
+
+            assert_eq!(flash.program_verify_crc(0, &[0xA5; 256]), Ok(()));
+            assert!(matches!(
+                flash.program_verify_crc(0, &[0x5A; 256]),
+                Err(FlashError::CrcMismatch { .. })
+            ));
+            assert_eq!(
+                flash.program_verify_crc(0x80, &[0xA5; 256]),
+                Err(FlashError::Unaligned)
+            );
+            assert_eq!(
+                flash.program_verify_crc(0x100, &[0xA5; 100]),
+                Err(FlashError::Unaligned)
+            );
+        }
+    }
+}
@@ -0,0 +1,892 @@
+//! Direct flash commands.
+//!
+//! The ROM doesn't provide a way to send arbitrary commands to the flash, so
+//! this is done by driving the XIP SSI and the QSPI chip select directly.
+//! As the SSI is RP2040 specific, this is not available with the `rp2350`
+//! feature.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use super::{cache_flush_barrier, FlashError, RomFlash};
+use crate::{rom_codes, ROM};
+
+/// XIP SSI (synchronous serial interface) base address
+const XIP_SSI_BASE: usize = 0x1800_0000;
+
+/// SSI control register 0
+const SSI_CTRLR0: *mut u32 = XIP_SSI_BASE as *mut u32;
+
+/// SSI control register 1 - number of data frames
+const SSI_CTRLR1: *mut u32 = (XIP_SSI_BASE + 0x04) as *mut u32;
+
+/// SSI enable register
+const SSI_SSIENR: *mut u32 = (XIP_SSI_BASE + 0x08) as *mut u32;
+
+/// SSI baud rate register - SPI clock divider
+const SSI_BAUDR: *mut u32 = (XIP_SSI_BASE + 0x14) as *mut u32;
+
+/// SSI SPI control register 0 - enhanced (dual/quad) SPI configuration
+const SSI_SPI_CTRLR0: *mut u32 = (XIP_SSI_BASE + 0xf4) as *mut u32;
+
+/// SSI CTRLR0 fields
+const SSI_CTRLR0_SPI_FRF_LSB: u32 = 21;
+const SSI_CTRLR0_SPI_FRF_QUAD: u32 = 0x2;
+const SSI_CTRLR0_DFS_32_LSB: u32 = 16;
+const SSI_CTRLR0_TMOD_LSB: u32 = 8;
+const SSI_CTRLR0_TMOD_BITS: u32 = 0x3 << SSI_CTRLR0_TMOD_LSB;
+const SSI_CTRLR0_TMOD_EEPROM_READ: u32 = 0x3;
+
+/// SSI SPI_CTRLR0 fields
+const SSI_SPI_CTRLR0_XIP_CMD_LSB: u32 = 24;
+const SSI_SPI_CTRLR0_WAIT_CYCLES_LSB: u32 = 11;
+const SSI_SPI_CTRLR0_WAIT_CYCLES_MAX: u8 = 0x1f;
+const SSI_SPI_CTRLR0_INST_L_LSB: u32 = 8;
+const SSI_SPI_CTRLR0_INST_L_NONE: u32 = 0x0;
+const SSI_SPI_CTRLR0_INST_L_8B: u32 = 0x2;
+const SSI_SPI_CTRLR0_ADDR_L_LSB: u32 = 2;
+const SSI_SPI_CTRLR0_ADDR_L_24B: u32 = 0x6;
+const SSI_SPI_CTRLR0_ADDR_L_32B: u32 = 0x8;
+const SSI_SPI_CTRLR0_TRANS_TYPE_LSB: u32 = 0;
+const SSI_SPI_CTRLR0_TRANS_TYPE_1C2A: u32 = 0x1;
+const SSI_SPI_CTRLR0_TRANS_TYPE_2C2A: u32 = 0x2;
+
+/// W25Q-family quad I/O fast read command, and the mode bits which follow
+/// the address to stay in continuous read mode
+const CONTINUOUS_READ_CMD: u8 = 0xEB;
+const CONTINUOUS_READ_MODE: u32 = 0xA0;
+
+/// Wait cycles after the mode bits for [`CONTINUOUS_READ_CMD`]
+const CONTINUOUS_READ_WAIT_CYCLES: u32 = 4;
+
+/// SPI clock divider used for fast XIP - 31.25MHz with a 125MHz system clock
+const FAST_XIP_CLKDIV: u8 = 4;
+
+/// SSI status register
+const SSI_SR: *const u32 = (XIP_SSI_BASE + 0x28) as *const u32;
+
+/// SSI data register 0
+const SSI_DR0: *mut u32 = (XIP_SSI_BASE + 0x60) as *mut u32;
+
+/// SSI status register: transmit FIFO not full
+const SSI_SR_TFNF: u32 = 1 << 1;
+
+/// SSI status register: receive FIFO not empty
+const SSI_SR_RFNE: u32 = 1 << 3;
+
+/// SSI FIFO depth, in entries
+const SSI_FIFO_DEPTH: usize = 16;
+
+/// IO QSPI bank base address
+const IO_QSPI_BASE: usize = 0x4001_8000;
+
+/// GPIO_QSPI_SS_CTRL register - controls the flash chip select
+const IO_QSPI_SS_CTRL: *mut u32 = (IO_QSPI_BASE + 0x0c) as *mut u32;
+
+/// GPIO_QSPI_SS_CTRL output override field
+const IO_QSPI_SS_CTRL_OUTOVER_LSB: u32 = 8;
+const IO_QSPI_SS_CTRL_OUTOVER_BITS: u32 = 0x3 << IO_QSPI_SS_CTRL_OUTOVER_LSB;
+const IO_QSPI_SS_CTRL_OUTOVER_LOW: u32 = 0x2;
+const IO_QSPI_SS_CTRL_OUTOVER_HIGH: u32 = 0x3;
+
+/// Smallest capacity code (log2 of size in bytes) accepted from a JEDEC ID.
+/// 4KB - a single erase sector.
+const JEDEC_MIN_CAPACITY_CODE: u8 = 12;
+
+/// Largest capacity code (log2 of size in bytes) accepted from a JEDEC ID.
+/// 16MB - the size of the RP2040's XIP window.
+const JEDEC_MAX_CAPACITY_CODE: u8 = 24;
+
+/// Public flash command functions
+impl ROM {
+    /// Sends an arbitrary serial command to the flash, and receives the
+    /// response.
+    ///
+    /// The transfer is full duplex: `max(txbuf.len(), rxbuf.len())` bytes
+    /// are clocked.  If `txbuf` is the shorter, it is padded with zeros, and
+    /// if `rxbuf` is the shorter, the additional received bytes are
+    /// discarded.  The first byte received is clocked in while the first
+    /// byte of `txbuf` (usually the command) is being sent.
+    ///
+    /// XIP is exited before the command is sent, and re-entered (in the slow
+    /// 03h command mode, see [`ROM::flash_enter_cmd_xip`]) afterwards.
+    ///
+    /// # Parameters
+    ///
+    /// * `txbuf` - Bytes to send to the flash
+    /// * `rxbuf` - Buffer for bytes received from the flash
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.
+    pub unsafe fn flash_do_cmd(txbuf: &[u8], rxbuf: &mut [u8]) {
+        let count = if txbuf.len() > rxbuf.len() {
+            txbuf.len()
+        } else {
+            rxbuf.len()
+        };
+        Self::flash_cmd(txbuf, rxbuf, count, 0);
+    }
+
+    /// Sends a serial command to the flash which has dummy clock cycles
+    /// between the command and data phases, such as the 0Bh fast read, and
+    /// receives the data phase.
+    ///
+    /// `txbuf` is sent, then `dummy_cycles` clocks are sent with the data
+    /// line held low, then `rxbuf.len()` bytes are received into `rxbuf`.
+    /// Unlike [`ROM::flash_do_cmd`], bytes received while `txbuf` is sent,
+    /// and during the dummy cycles, are discarded.
+    ///
+    /// After `flash_exit_xip`, the ROM leaves the SSI in standard (single
+    /// data line) SPI mode, with 8 bit data frames, transmitting and
+    /// receiving.  Its enhanced SPI wait cycle setting only applies to dual
+    /// and quad reads, so in this mode every frame is 8 clocks, and the
+    /// dummy cycles are sent as whole zero bytes.
+    ///
+    /// XIP is exited before the command is sent, and re-entered (in the slow
+    /// 03h command mode, see [`ROM::flash_enter_cmd_xip`]) afterwards.
+    ///
+    /// # Parameters
+    ///
+    /// * `txbuf` - Bytes to send to the flash - the command, and any address
+    /// * `dummy_cycles` - Clocks between the command and data phases, a
+    ///   multiple of 8
+    /// * `rxbuf` - Buffer for the data phase
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::InvalidDummyCycles`] if `dummy_cycles` isn't a multiple
+    /// of 8, in which case no command is sent.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_do_cmd`].
+    pub unsafe fn flash_do_cmd_ex(
+        txbuf: &[u8],
+        dummy_cycles: u8,
+        rxbuf: &mut [u8],
+    ) -> Result<(), FlashError> {
+        if !dummy_cycles.is_multiple_of(8) {
+            return Err(FlashError::InvalidDummyCycles(dummy_cycles));
+        }
+
+        let skip = txbuf.len() + dummy_cycles as usize / 8;
+        Self::flash_cmd(txbuf, rxbuf, skip + rxbuf.len(), skip);
+        Ok(())
+    }
+
+    /// Detects the size of the flash, by reading its JEDEC ID.
+    ///
+    /// The capacity is taken from the third JEDEC ID byte, which by
+    /// convention is log2 of the device size in bytes.
+    ///
+    /// # Returns
+    ///
+    /// The flash size in bytes, or [`FlashError::InvalidJedecId`] if no flash
+    /// responded or the reported capacity is implausible (smaller than 4KB
+    /// or larger than the 16MB XIP window).
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_do_cmd`].
+    pub unsafe fn flash_size() -> Result<u32, FlashError> {
+        const FLASH_CMD_READ_JEDEC_ID: u8 = 0x9F;
+
+        // One byte for the command, three for the ID
+        let txbuf = [FLASH_CMD_READ_JEDEC_ID];
+        let mut rxbuf = [0u8; 4];
+        Self::flash_do_cmd(&txbuf, &mut rxbuf);
+        let id = [rxbuf[1], rxbuf[2], rxbuf[3]];
+
+        // An absent device, or floating data line, reads as all 0s or all
+        // 1s, so also sanity check the manufacturer
+        let manufacturer = id[0];
+        let capacity_code = id[2];
+        if manufacturer == 0x00
+            || manufacturer == 0xFF
+            || !(JEDEC_MIN_CAPACITY_CODE..=JEDEC_MAX_CAPACITY_CODE).contains(&capacity_code)
+        {
+            return Err(FlashError::InvalidJedecId(id));
+        }
+
+        Ok(1 << capacity_code)
+    }
+
+    /// Reads the 64-bit unique ID of the flash device, using the 4Bh
+    /// command.
+    ///
+    /// The RP2040 has no unique ID of its own, so the flash's ID is the
+    /// standard way to get a stable per-board serial number, for example for
+    /// USB descriptors.  Requires a flash device supporting 4Bh, such as the
+    /// W25Q series fitted to most RP2040 boards.
+    ///
+    /// # Returns
+    ///
+    /// The unique ID, or [`FlashError::InvalidUniqueId`] if the ID read as
+    /// all zeros, which usually means no flash is connected.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_do_cmd`].
+    pub unsafe fn chip_unique_id() -> Result<[u8; 8], FlashError> {
+        const FLASH_CMD_READ_UNIQUE_ID: u8 = 0x4B;
+        const DUMMY_CYCLES: u8 = 32;
+
+        // The command, followed by 32 dummy cycles, then the ID is clocked
+        // out.  DUMMY_CYCLES is a multiple of 8, so this can't fail.
+        let mut id = [0u8; 8];
+        let _ = Self::flash_do_cmd_ex(&[FLASH_CMD_READ_UNIQUE_ID], DUMMY_CYCLES, &mut id);
+
+        if id.iter().all(|&byte| byte == 0) {
+            return Err(FlashError::InvalidUniqueId);
+        }
+
+        Ok(id)
+    }
+
+    /// Reads the flash's unique ID, as [`ROM::chip_unique_id`], and writes it
+    /// into `buf` as 16 uppercase hex digits, suitable for a USB
+    /// `iSerialNumber` string descriptor.
+    ///
+    /// # Returns
+    ///
+    /// The serial number, as a view of `buf`, or
+    /// [`FlashError::InvalidUniqueId`] if the ID read as all zeros, in which
+    /// case `buf` is unchanged.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_do_cmd`].
+    pub unsafe fn board_serial(buf: &mut [u8; 16]) -> Result<&str, FlashError> {
+        let id = Self::chip_unique_id()?;
+        Ok(hex_upper(&id, buf))
+    }
+
+    /// Returns a slice over the entire contents of flash, as mapped into the
+    /// XIP window at `0x1000_0000`.
+    ///
+    /// The flash size is detected using [`ROM::flash_size`], so the slice
+    /// never extends beyond the end of the flash device.  Use
+    /// [`ROM::flash_contents_len`] if the size is already known, to avoid
+    /// the flash command this requires.
+    ///
+    /// # Returns
+    ///
+    /// The flash contents, or an error if the flash size could not be
+    /// detected.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_size`] and [`ROM::flash_contents_len`].
+    pub unsafe fn flash_contents() -> Result<&'static [u8], FlashError> {
+        let size = Self::flash_size()?;
+        Ok(Self::flash_contents_len(size as usize))
+    }
+
+    /// Configures the SSI for fast quad I/O XIP reads, restoring full speed
+    /// execution from flash after it has been modified, without a reboot.
+    ///
+    /// [`ROM::flash_enter_cmd_xip`] (and so every other function in this
+    /// crate which modifies flash) leaves XIP in a slow single SPI 03h read
+    /// mode.  This is essentially a reimplementation of a typical boot2.
+    /// Each XIP access sends `cmd` serially, then the 24 bit address over
+    /// all 4 data lines, waits `dummy_cycles` clocks, and reads the data
+    /// over all 4 data lines.
+    ///
+    /// For the standard EBh quad I/O fast read on W25Q-family devices, the
+    /// two clocks of mode bits which follow the address are included in
+    /// `dummy_cycles`, so `(0xEB, 6)` is appropriate.  The mode bits aren't
+    /// driven, so devices which may enter a continuous read mode based on
+    /// them are not supported - see [`ROM::flash_enter_xip_fast`] for
+    /// that.  Timing, and whether EBh is supported at all,
+    /// differs between flash devices - check your device's datasheet.
+    ///
+    /// The flash must have its quad enable (QE) bit set.  This is
+    /// non-volatile on most devices, and is set by the standard boot2s.
+    ///
+    /// There is no QPI equivalent, where the command and address are also
+    /// sent over 4 lines.  The ROM has no QPI sequences for any flash
+    /// device, as its flash functions are all serial and the only chip
+    /// specific code is in boot2, and each device enters and exits QPI with
+    /// its own commands (38h and FFh on W25Q-family devices).  Once a device is in
+    /// QPI mode it no longer understands the serial commands the ROM sends,
+    /// so every ROM flash function, including erasing and programming, would
+    /// hang or corrupt flash until it was taken out of QPI mode again.  The
+    /// single serial command byte costs only 8 clocks per XIP cache miss, so
+    /// this quad I/O mode gets most of QPI's bandwidth.
+    ///
+    /// # Parameters
+    ///
+    /// * `cmd` - The read command, e.g. `0xEB`
+    /// * `dummy_cycles` - Clocks between the address and data phases, at
+    ///   most 31
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.  If the flash doesn't
+    /// support the command, executing from flash afterwards will fault.
+    pub unsafe fn flash_enter_fast_xip(cmd: u8, dummy_cycles: u8) {
+        Self::flash_enter_quad_xip(cmd, dummy_cycles, FAST_XIP_CLKDIV);
+    }
+
+    /// Enters the ROM's slow 03h command XIP mode, as
+    /// [`ROM::flash_enter_cmd_xip`], but with the SPI clock divided from the
+    /// system clock by `clkdiv`, rather than the ROM's default.
+    ///
+    /// For flash which is unreliable at the clock the ROM or boot2 uses, or
+    /// to run the flash faster than the ROM's conservative default.  The
+    /// SSI divides by even values only.
+    ///
+    /// # Parameters
+    ///
+    /// * `clkdiv` - The SPI clock divider, even and at least 2
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::InvalidClockDivider`] if `clkdiv` is zero or odd, in
+    /// which case XIP is left as it was.
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.  If the flash can't run at
+    /// the resulting clock, executing from flash afterwards will fault.
+    pub unsafe fn flash_enter_xip_with_clkdiv(clkdiv: u8) -> Result<(), FlashError> {
+        if clkdiv == 0 || !clkdiv.is_multiple_of(2) {
+            return Err(FlashError::InvalidClockDivider(clkdiv));
+        }
+
+        Self::flash_enter_cmd_xip_clkdiv(clkdiv);
+        Ok(())
+    }
+
+    /// Enters a fast quad I/O XIP mode using the flash's continuous read
+    /// mode, as the standard W25Q-family boot2 does.
+    ///
+    /// Like [`ROM::flash_enter_fast_xip`] with `(0xEB, 6)`, but the mode
+    /// bits following the address are driven to `A0h`, which tells the
+    /// flash to expect another read.  Once in continuous read mode the
+    /// flash omits the command byte from each XIP access, saving 8 clocks
+    /// per cache miss, which matters most for code such as interrupt
+    /// handlers which is fetched from flash after the cache has been
+    /// evicted.  The clock divider is as for [`ROM::flash_enter_fast_xip`].
+    ///
+    /// Supported by W25Q-family devices, and others which use mode bits
+    /// `Ax` (`M5-4` = `10`) to select continuous read after an EBh command.
+    /// Devices which don't, may be left in an unknown state.
+    ///
+    /// While the flash is in continuous read mode it doesn't recognise
+    /// serial commands.  The ROM's `flash_exit_xip`, which every function in
+    /// this crate which modifies flash or sends a flash command calls
+    /// first, takes it out of continuous read mode, as does
+    /// [`ROM::flash_exit_xip_fast`].
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.  The flash must have its
+    /// quad enable (QE) bit set.  If it doesn't support continuous read
+    /// mode, executing from flash afterwards will fault.
+    pub unsafe fn flash_enter_xip_fast() {
+        Self::flash_enter_continuous_xip(FAST_XIP_CLKDIV);
+    }
+
+    /// Takes the flash out of continuous read mode, entered by
+    /// [`ROM::flash_enter_xip_fast`], and returns XIP to the ROM's slow 03h
+    /// command mode, as [`ROM::flash_enter_cmd_xip`].
+    ///
+    /// Use this before driving the flash by other means than this crate,
+    /// such as another driver which sends serial commands.  It is harmless
+    /// if the flash wasn't in continuous read mode.
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.
+    pub unsafe fn flash_exit_xip_fast() {
+        Self::flash_exit_continuous_xip();
+    }
+
+    /// Returns whether XIP is currently enabled, so that reads from the XIP
+    /// window at [`XIP_BASE`](crate::XIP_BASE) reach the flash.
+    ///
+    /// Checks that the XIP SSI is enabled and in its XIP (EEPROM read)
+    /// transfer mode, as it is after boot2, [`ROM::flash_enter_cmd_xip`] or
+    /// [`ROM::flash_enter_fast_xip`], and not in the serial mode
+    /// [`ROM::flash_exit_xip`] leaves it in.  If code is running from flash,
+    /// this is always `true`, so this is for RAM resident code, such as a
+    /// flash sequence deciding whether it needs to re-enter XIP.  It is
+    /// always inlined, so it doesn't itself run from flash.
+    ///
+    /// Enabled doesn't mean the XIP cache is coherent with the flash: after
+    /// erasing or programming, the cache must still be flushed, with
+    /// [`ROM::flash_flush_cache`], as every flash function in this crate
+    /// does.  Nor is this the XIP controller's `CTRL.EN` bit, which only
+    /// enables the cache - with it clear, XIP accesses go straight to the
+    /// flash.
+    #[inline(always)]
+    pub fn xip_enabled() -> bool {
+        // Safety: reading the SSI registers has no side effects
+        let (enabled, ctrlr0) = unsafe {
+            (
+                core::ptr::read_volatile(SSI_SSIENR),
+                core::ptr::read_volatile(SSI_CTRLR0),
+            )
+        };
+
+        enabled & 1 == 1
+            && (ctrlr0 & SSI_CTRLR0_TMOD_BITS)
+                == (SSI_CTRLR0_TMOD_EEPROM_READ << SSI_CTRLR0_TMOD_LSB)
+    }
+}
+
+// Private flash functions
+impl ROM {
+    // Exit XIP and clock `count` bytes to and from the flash, sending
+    // `txbuf` padded with zeros, and storing the bytes received from
+    // `rx_skip` onwards in `rxbuf`, then re-enter XIP.  See flash_do_cmd().
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn flash_cmd(txbuf: &[u8], rxbuf: &mut [u8], count: usize, rx_skip: usize) {
+        type RomFlashFn = unsafe extern "C" fn();
+
+        // Look up all the ROM functions we need before exiting XIP, as the
+        // lookup code lives in flash
+        let connect_internal_flash: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH));
+        let flash_exit_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP));
+        let flash_flush_cache: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE));
+        let flash_enter_cmd_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_ENTER_CMD_XIP));
+
+        connect_internal_flash();
+        flash_exit_xip();
+
+        Self::flash_cs_force(false);
+
+        let mut tx_remaining = count;
+        let mut rx_remaining = count;
+
+        // Limit the bytes in flight, so the RX FIFO can't overflow if we're
+        // interrupted
+        const MAX_IN_FLIGHT: usize = SSI_FIFO_DEPTH - 2;
+
+        while tx_remaining > 0 || rx_remaining > 0 {
+            let flags = core::ptr::read_volatile(SSI_SR);
+            let can_put = flags & SSI_SR_TFNF != 0;
+            let can_get = flags & SSI_SR_RFNE != 0;
+
+            if can_put && tx_remaining > 0 && rx_remaining - tx_remaining < MAX_IN_FLIGHT {
+                let index = count - tx_remaining;
+                let byte = if index < txbuf.len() {
+                    *txbuf.as_ptr().add(index)
+                } else {
+                    0
+                };
+                core::ptr::write_volatile(SSI_DR0, byte as u32);
+                tx_remaining -= 1;
+            }
+
+            if can_get && rx_remaining > 0 {
+                let index = count - rx_remaining;
+                let byte = core::ptr::read_volatile(SSI_DR0) as u8;
+                if index >= rx_skip && index - rx_skip < rxbuf.len() {
+                    *rxbuf.as_mut_ptr().add(index - rx_skip) = byte;
+                }
+                rx_remaining -= 1;
+            }
+        }
+
+        Self::flash_cs_force(true);
+
+        flash_flush_cache();
+        cache_flush_barrier();
+        flash_enter_cmd_xip();
+    }
+
+    // Exit XIP and reconfigure the SSI for quad I/O XIP reads, with the
+    // given SPI clock divider.  See flash_enter_fast_xip().
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn flash_enter_quad_xip(cmd: u8, dummy_cycles: u8, clkdiv: u8) {
+        type RomFlashFn = unsafe extern "C" fn();
+
+        // Look up all the ROM functions we need before exiting XIP, as the
+        // lookup code lives in flash
+        let connect_internal_flash: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH));
+        let flash_exit_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP));
+        let flash_flush_cache: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE));
+
+        // Exiting XIP also takes the flash out of any continuous read mode
+        // boot2 may have left it in
+        connect_internal_flash();
+        flash_exit_xip();
+
+        // The SSI must be disabled while it is reconfigured
+        core::ptr::write_volatile(SSI_SSIENR, 0);
+
+        core::ptr::write_volatile(SSI_BAUDR, clkdiv as u32);
+
+        // Quad SPI, 32 bit data frames, send instruction and address, then
+        // receive data
+        core::ptr::write_volatile(
+            SSI_CTRLR0,
+            (SSI_CTRLR0_SPI_FRF_QUAD << SSI_CTRLR0_SPI_FRF_LSB)
+                | (31 << SSI_CTRLR0_DFS_32_LSB)
+                | (SSI_CTRLR0_TMOD_EEPROM_READ << SSI_CTRLR0_TMOD_LSB),
+        );
+
+        // Receive a single data frame per transfer
+        core::ptr::write_volatile(SSI_CTRLR1, 0);
+
+        // Serial 8 bit command, then quad 24 bit address, wait cycles and
+        // data
+        let dummy_cycles = if dummy_cycles > SSI_SPI_CTRLR0_WAIT_CYCLES_MAX {
+            SSI_SPI_CTRLR0_WAIT_CYCLES_MAX
+        } else {
+            dummy_cycles
+        };
+        core::ptr::write_volatile(
+            SSI_SPI_CTRLR0,
+            ((cmd as u32) << SSI_SPI_CTRLR0_XIP_CMD_LSB)
+                | ((dummy_cycles as u32) << SSI_SPI_CTRLR0_WAIT_CYCLES_LSB)
+                | (SSI_SPI_CTRLR0_INST_L_8B << SSI_SPI_CTRLR0_INST_L_LSB)
+                | (SSI_SPI_CTRLR0_ADDR_L_24B << SSI_SPI_CTRLR0_ADDR_L_LSB)
+                | (SSI_SPI_CTRLR0_TRANS_TYPE_1C2A << SSI_SPI_CTRLR0_TRANS_TYPE_LSB),
+        );
+
+        core::ptr::write_volatile(SSI_SSIENR, 1);
+
+        flash_flush_cache();
+        cache_flush_barrier();
+    }
+
+    // Exit XIP, then put the flash into continuous read mode with a single
+    // EBh read, and configure the SSI to send only the address and mode
+    // bits on each XIP access.  See flash_enter_xip_fast().
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn flash_enter_continuous_xip(clkdiv: u8) {
+        type RomFlashFn = unsafe extern "C" fn();
+
+        // Look up all the ROM functions we need before exiting XIP, as the
+        // lookup code lives in flash
+        let connect_internal_flash: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH));
+        let flash_exit_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP));
+        let flash_flush_cache: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE));
+
+        connect_internal_flash();
+        flash_exit_xip();
+
+        // The SSI must be disabled while it is reconfigured
+        core::ptr::write_volatile(SSI_SSIENR, 0);
+
+        core::ptr::write_volatile(SSI_BAUDR, clkdiv as u32);
+
+        // Quad SPI, 32 bit data frames, send instruction and address, then
+        // receive data
+        core::ptr::write_volatile(
+            SSI_CTRLR0,
+            (SSI_CTRLR0_SPI_FRF_QUAD << SSI_CTRLR0_SPI_FRF_LSB)
+                | (31 << SSI_CTRLR0_DFS_32_LSB)
+                | (SSI_CTRLR0_TMOD_EEPROM_READ << SSI_CTRLR0_TMOD_LSB),
+        );
+
+        // Receive a single data frame per transfer
+        core::ptr::write_volatile(SSI_CTRLR1, 0);
+
+        // Serial 8 bit command, then quad 24 bit address and 8 mode bits,
+        // sent together as a 32 bit address, wait cycles and data
+        let spi_ctrlr0 = (CONTINUOUS_READ_WAIT_CYCLES << SSI_SPI_CTRLR0_WAIT_CYCLES_LSB)
+            | (SSI_SPI_CTRLR0_ADDR_L_32B << SSI_SPI_CTRLR0_ADDR_L_LSB);
+        core::ptr::write_volatile(
+            SSI_SPI_CTRLR0,
+            spi_ctrlr0
+                | (SSI_SPI_CTRLR0_INST_L_8B << SSI_SPI_CTRLR0_INST_L_LSB)
+                | (SSI_SPI_CTRLR0_TRANS_TYPE_1C2A << SSI_SPI_CTRLR0_TRANS_TYPE_LSB),
+        );
+
+        core::ptr::write_volatile(SSI_SSIENR, 1);
+
+        // A single read from address 0 with the continuous read mode bits,
+        // discarding the data
+        core::ptr::write_volatile(SSI_DR0, CONTINUOUS_READ_CMD as u32);
+        core::ptr::write_volatile(SSI_DR0, CONTINUOUS_READ_MODE);
+        while core::ptr::read_volatile(SSI_SR) & SSI_SR_RFNE == 0 {}
+        core::ptr::read_volatile(SSI_DR0);
+
+        core::ptr::write_volatile(SSI_SSIENR, 0);
+
+        // From now on, no instruction, and the mode bits are sent after the
+        // address from the XIP command field, keeping the flash in
+        // continuous read mode
+        core::ptr::write_volatile(
+            SSI_SPI_CTRLR0,
+            spi_ctrlr0
+                | (CONTINUOUS_READ_MODE << SSI_SPI_CTRLR0_XIP_CMD_LSB)
+                | (SSI_SPI_CTRLR0_INST_L_NONE << SSI_SPI_CTRLR0_INST_L_LSB)
+                | (SSI_SPI_CTRLR0_TRANS_TYPE_2C2A << SSI_SPI_CTRLR0_TRANS_TYPE_LSB),
+        );
+
+        core::ptr::write_volatile(SSI_SSIENR, 1);
+
+        flash_flush_cache();
+        cache_flush_barrier();
+    }
+
+    // Exit XIP, which also exits continuous read mode, and re-enter the
+    // ROM's command XIP mode.  See flash_exit_xip_fast().
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn flash_exit_continuous_xip() {
+        type RomFlashFn = unsafe extern "C" fn();
+
+        // Look up all the ROM functions we need before exiting XIP, as the
+        // lookup code lives in flash
+        let connect_internal_flash: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH));
+        let flash_exit_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP));
+        let flash_flush_cache: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE));
+        let flash_enter_cmd_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_ENTER_CMD_XIP));
+
+        connect_internal_flash();
+        flash_exit_xip();
+        flash_enter_cmd_xip();
+
+        flash_flush_cache();
+        cache_flush_barrier();
+    }
+
+    // Re-enter the ROM's command XIP mode, then replace the SPI clock
+    // divider.  See flash_enter_xip_with_clkdiv().
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn flash_enter_cmd_xip_clkdiv(clkdiv: u8) {
+        type RomFlashFn = unsafe extern "C" fn();
+
+        // Look up all the ROM functions we need before exiting XIP, as the
+        // lookup code lives in flash
+        let connect_internal_flash: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::CONNECT_INTERNAL_FLASH));
+        let flash_exit_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_EXIT_XIP));
+        let flash_flush_cache: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_FLUSH_CACHE));
+        let flash_enter_cmd_xip: RomFlashFn =
+            core::mem::transmute(Self::rom_func_lookup(rom_codes::FLASH_ENTER_CMD_XIP));
+
+        connect_internal_flash();
+        flash_exit_xip();
+        flash_enter_cmd_xip();
+
+        // The SSI must be disabled while the divider is changed
+        core::ptr::write_volatile(SSI_SSIENR, 0);
+        core::ptr::write_volatile(SSI_BAUDR, clkdiv as u32);
+        core::ptr::write_volatile(SSI_SSIENR, 1);
+
+        flash_flush_cache();
+        cache_flush_barrier();
+    }
+
+    // Drive the flash chip select low (selected) or high (deselected),
+    // overriding the SSI's own control of it
+    #[inline(always)]
+    unsafe fn flash_cs_force(high: bool) {
+        let value = if high {
+            IO_QSPI_SS_CTRL_OUTOVER_HIGH
+        } else {
+            IO_QSPI_SS_CTRL_OUTOVER_LOW
+        };
+        let reg = core::ptr::read_volatile(IO_QSPI_SS_CTRL);
+        let reg = (reg & !IO_QSPI_SS_CTRL_OUTOVER_BITS) | (value << IO_QSPI_SS_CTRL_OUTOVER_LSB);
+        core::ptr::write_volatile(IO_QSPI_SS_CTRL, reg);
+    }
+}
+
+// Writes `bytes` into `buf` as uppercase hex, returning it as a string
+fn hex_upper<'a>(bytes: &[u8; 8], buf: &'a mut [u8; 16]) -> &'a str {
+    const DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    for (byte, pair) in bytes.iter().zip(buf.chunks_exact_mut(2)) {
+        pair[0] = DIGITS[(byte >> 4) as usize];
+        pair[1] = DIGITS[(byte & 0xf) as usize];
+    }
+
+    // Only ASCII hex digits have been written, so this can't fail
+    core::str::from_utf8(buf).unwrap_or_default()
+}
+
+/// Public flash command functions
+impl RomFlash {
+    /// See [`ROM::xip_enabled`].
+    #[inline(always)]
+    pub fn xip_enabled() -> bool {
+        ROM::xip_enabled()
+    }
+
+    /// See [`ROM::flash_do_cmd`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_do_cmd`].
+    #[inline]
+    pub unsafe fn flash_do_cmd(txbuf: &[u8], rxbuf: &mut [u8]) {
+        ROM::flash_do_cmd(txbuf, rxbuf)
+    }
+
+    /// See [`ROM::flash_do_cmd_ex`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_do_cmd_ex`].
+    #[inline]
+    pub unsafe fn flash_do_cmd_ex(
+        txbuf: &[u8],
+        dummy_cycles: u8,
+        rxbuf: &mut [u8],
+    ) -> Result<(), FlashError> {
+        ROM::flash_do_cmd_ex(txbuf, dummy_cycles, rxbuf)
+    }
+
+    /// See [`ROM::flash_size`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_size`].
+    #[inline]
+    pub unsafe fn flash_size() -> Result<u32, FlashError> {
+        ROM::flash_size()
+    }
+
+    /// See [`ROM::chip_unique_id`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::chip_unique_id`].
+    #[inline]
+    pub unsafe fn chip_unique_id() -> Result<[u8; 8], FlashError> {
+        ROM::chip_unique_id()
+    }
+
+    /// See [`ROM::board_serial`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::board_serial`].
+    #[inline]
+    pub unsafe fn board_serial(buf: &mut [u8; 16]) -> Result<&str, FlashError> {
+        ROM::board_serial(buf)
+    }
+
+    /// See [`ROM::flash_contents`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_contents`].
+    #[inline]
+    pub unsafe fn flash_contents() -> Result<&'static [u8], FlashError> {
+        ROM::flash_contents()
+    }
+
+    /// See [`ROM::flash_enter_fast_xip`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_enter_fast_xip`].
+    #[inline]
+    pub unsafe fn flash_enter_fast_xip(cmd: u8, dummy_cycles: u8) {
+        ROM::flash_enter_fast_xip(cmd, dummy_cycles)
+    }
+
+    /// See [`ROM::flash_enter_xip_with_clkdiv`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_enter_xip_with_clkdiv`].
+    #[inline]
+    pub unsafe fn flash_enter_xip_with_clkdiv(clkdiv: u8) -> Result<(), FlashError> {
+        ROM::flash_enter_xip_with_clkdiv(clkdiv)
+    }
+
+    /// See [`ROM::flash_enter_xip_fast`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_enter_xip_fast`].
+    #[inline]
+    pub unsafe fn flash_enter_xip_fast() {
+        ROM::flash_enter_xip_fast()
+    }
+
+    /// See [`ROM::flash_exit_xip_fast`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_exit_xip_fast`].
+    #[inline]
+    pub unsafe fn flash_exit_xip_fast() {
+        ROM::flash_exit_xip_fast()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_upper_formats_serial() {
+        let mut buf = [0u8; 16];
+        let id = [0xE6, 0x60, 0x58, 0x38, 0x83, 0x1A, 0x2B, 0x0F];
+        assert_eq!(hex_upper(&id, &mut buf), "E6605838831A2B0F");
+    }
+
+    #[test]
+    fn flash_do_cmd_ex_rejects_partial_dummy_bytes() {
+        // Rejected before the SSI is touched
+        let mut rxbuf = [0u8; 4];
+        assert_eq!(
+            unsafe { ROM::flash_do_cmd_ex(&[0x0B, 0, 0, 0], 7, &mut rxbuf) },
+            Err(FlashError::InvalidDummyCycles(7))
+        );
+    }
+
+    #[test]
+    fn flash_enter_xip_with_clkdiv_rejects_bad_dividers() {
+        // Rejected before XIP is disabled
+        for clkdiv in [0, 3] {
+            assert_eq!(
+                unsafe { ROM::flash_enter_xip_with_clkdiv(clkdiv) },
+                Err(FlashError::InvalidClockDivider(clkdiv))
+            );
+        }
+    }
+}
@@ -0,0 +1,1087 @@
+//! Soft-float functions from the RP2040 ROM.
+//!
+//! The ROM contains a fast floating point library, accessed through tables
+//! of function pointers found via the data table: `SF` for single precision
+//! and `SD` for double precision.  The B0 ROM (version 1) has a shorter
+//! single precision table, and no double precision table.
+//!
+//! The ROM functions flush denormal inputs and outputs to zero.
+//!
+//! Neither table has a fused multiply-add, in any ROM version, so there is
+//! no ROM backed `fma`.  Use `a * b + c`, which rounds twice, or a software
+//! implementation such as `libm`'s `fmaf` and `fma` where the single
+//! rounding matters.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use core::cmp::Ordering;
+
+use crate::{rom_codes, RomError, ROM};
+
+/// Size of the version 1 (B0 ROM) soft-float table, in bytes.  Functions at
+/// or beyond this offset require a version 2 ROM or later.
+const SF_TABLE_V1_SIZE: usize = 0x54;
+
+/// Offsets of functions within the soft-float table
+pub(crate) const SF_TABLE_FADD: usize = 0x00;
+pub(crate) const SF_TABLE_FSUB: usize = 0x04;
+pub(crate) const SF_TABLE_FMUL: usize = 0x08;
+pub(crate) const SF_TABLE_FDIV: usize = 0x0c;
+pub(crate) const SF_TABLE_FSQRT: usize = 0x18;
+pub(crate) const SF_TABLE_FLOAT2INT: usize = 0x1c;
+pub(crate) const SF_TABLE_INT2FLOAT: usize = 0x2c;
+const SF_TABLE_UINT2FLOAT: usize = 0x34;
+pub(crate) const SF_TABLE_FCOS: usize = 0x3c;
+pub(crate) const SF_TABLE_FSIN: usize = 0x40;
+pub(crate) const SF_TABLE_FTAN: usize = 0x44;
+pub(crate) const SF_TABLE_FEXP: usize = 0x4c;
+pub(crate) const SF_TABLE_FLN: usize = 0x50;
+pub(crate) const SF_TABLE_FCMP: usize = 0x54;
+const SF_TABLE_FATAN2: usize = 0x58;
+
+/// The sign bit of an IEEE 754 double
+#[cfg(feature = "soft-double")]
+const DOUBLE_SIGN_BIT: u64 = 1 << 63;
+
+/// Offsets of functions within the double precision soft-float table
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DADD: usize = 0x00;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DMUL: usize = 0x08;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DDIV: usize = 0x0c;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DOUBLE2FIX: usize = 0x20;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_FIX2DOUBLE: usize = 0x30;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_UINT2DOUBLE: usize = 0x34;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DEXP: usize = 0x4c;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DLN: usize = 0x50;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DCMP: usize = 0x54;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DATAN2: usize = 0x58;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_INT642DOUBLE: usize = 0x5c;
+#[cfg(feature = "soft-double")]
+const SD_TABLE_DOUBLE2INT64: usize = 0x6c;
+
+/// The soft-float functions, on their own.
+///
+/// A zero-sized namespace containing only the soft-float functions of
+/// [`ROM`], which it calls.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RomMath;
+
+/// Public soft-float functions
+impl ROM {
+    /// Converts a float to a signed integer.
+    ///
+    /// The ROM rounds towards negative infinity (like `floor`), unlike Rust's
+    /// `as` conversion, which rounds towards zero.  So `-1.5` converts to
+    /// `-2`, not `-1`.  Out of range values are clamped to `i32::MIN` and
+    /// `i32::MAX`.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn float_to_int(x: f32) -> i32 {
+        type RomFloat2IntFn = unsafe extern "C" fn(x: f32) -> i32;
+
+        let func: RomFloat2IntFn = core::mem::transmute(Self::sf_table_func(SF_TABLE_FLOAT2INT));
+        func(x)
+    }
+
+    /// Converts a signed integer to a float, rounding to the nearest
+    /// representable value.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn int_to_float(x: i32) -> f32 {
+        type RomInt2FloatFn = unsafe extern "C" fn(x: i32) -> f32;
+
+        let func: RomInt2FloatFn = core::mem::transmute(Self::sf_table_func(SF_TABLE_INT2FLOAT));
+        func(x)
+    }
+
+    /// Converts an unsigned integer to a float, rounding to the nearest
+    /// representable value.
+    ///
+    /// Values of 2^31 and above, such as large timestamps, can't be
+    /// converted with [`ROM::int_to_float`].
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn uint_to_float(x: u32) -> f32 {
+        type RomUint2FloatFn = unsafe extern "C" fn(x: u32) -> f32;
+
+        let func: RomUint2FloatFn = core::mem::transmute(Self::sf_table_func(SF_TABLE_UINT2FLOAT));
+        func(x)
+    }
+
+    /// Compares two floats.
+    ///
+    /// The ROM comparison has no "unordered" result, so the result is not
+    /// meaningful if either operand is NaN - check with `is_nan()` first if
+    /// NaNs are possible.  As denormals are flushed to zero, they compare
+    /// equal to zero.
+    ///
+    /// # Returns
+    ///
+    /// The ordering of `a` relative to `b`, or
+    /// [`RomError::UnsupportedRomVersion`] on a B0 (version 1) ROM, which
+    /// lacks this function.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn fcmp(a: f32, b: f32) -> Result<Ordering, RomError> {
+        type RomFcmpFn = unsafe extern "C" fn(a: f32, b: f32) -> i32;
+
+        let func: RomFcmpFn = core::mem::transmute(Self::sf_table_func_v2(SF_TABLE_FCMP)?);
+        Ok(func(a, b).cmp(&0))
+    }
+
+    /// Returns the sine of `x`, in radians.
+    ///
+    /// Only accurate for `x` in the range -128 to 128, where the result is
+    /// within a few units in the last place - less accurate than `libm`, but
+    /// much smaller and faster.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn fsin(x: f32) -> f32 {
+        Self::sf_unary(SF_TABLE_FSIN, x)
+    }
+
+    /// Returns the cosine of `x`, in radians.
+    ///
+    /// Accuracy is as for [`ROM::fsin`].
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn fcos(x: f32) -> f32 {
+        Self::sf_unary(SF_TABLE_FCOS, x)
+    }
+
+    /// Returns the tangent of `x`, in radians.
+    ///
+    /// Accuracy is as for [`ROM::fsin`].
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn ftan(x: f32) -> f32 {
+        Self::sf_unary(SF_TABLE_FTAN, x)
+    }
+
+    /// Returns `e` raised to the power `x`.
+    ///
+    /// The result is within a few units in the last place, and overflows to
+    /// infinity, or underflows to zero, as denormals are flushed.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn fexp(x: f32) -> f32 {
+        Self::sf_unary(SF_TABLE_FEXP, x)
+    }
+
+    /// Returns the natural logarithm of `x`.
+    ///
+    /// The result is within a few units in the last place.  Negative inputs
+    /// return NaN, and zero returns negative infinity.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn fln(x: f32) -> f32 {
+        Self::sf_unary(SF_TABLE_FLN, x)
+    }
+
+    /// Returns the four quadrant arctangent of `y` and `x`, in radians.
+    ///
+    /// # Returns
+    ///
+    /// The angle, in the range -pi to pi, or
+    /// [`RomError::UnsupportedRomVersion`] on a B0 (version 1) ROM, which
+    /// lacks this function.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn fatan2(y: f32, x: f32) -> Result<f32, RomError> {
+        type RomFatan2Fn = unsafe extern "C" fn(y: f32, x: f32) -> f32;
+
+        let func: RomFatan2Fn = core::mem::transmute(Self::sf_table_func_v2(SF_TABLE_FATAN2)?);
+        Ok(func(y, x))
+    }
+
+    /// Divides `a` by `b`, using the ROM's `fdiv`, unless `b` is zero.
+    ///
+    /// The ROM returns infinity or NaN when dividing by zero, which can then
+    /// propagate silently.  As the ROM flushes denormals to zero, a denormal
+    /// `b` is treated as zero too.  NaN arguments still give NaN.
+    ///
+    /// # Returns
+    ///
+    /// The quotient, or `None` if `b` is zero or denormal.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn float_div_checked(a: f32, b: f32) -> Option<f32> {
+        type RomFdivFn = unsafe extern "C" fn(a: f32, b: f32) -> f32;
+
+        if b == 0.0 || b.is_subnormal() {
+            return None;
+        }
+
+        let func: RomFdivFn = core::mem::transmute(Self::sf_table_func(SF_TABLE_FDIV));
+        Some(func(a, b))
+    }
+
+    /// Returns the base 10 logarithm of `x`, for example for decibel
+    /// calculations.
+    ///
+    /// The ROM has no `log10`, so this is the ROM's `fln`, multiplied by
+    /// `1 / ln(10)` with the ROM's `fmul`, giving a slightly larger error
+    /// than `fln` alone.
+    /// Negative inputs return NaN, and zero returns negative infinity.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn float_log10(x: f32) -> f32 {
+        Self::sf_binary(SF_TABLE_FMUL, Self::fln(x), core::f32::consts::LOG10_E)
+    }
+
+    /// Returns the base 2 logarithm of `x`, for example for entropy
+    /// calculations.
+    ///
+    /// The ROM has no `log2`, so this is the ROM's `fln`, multiplied by the
+    /// compile time constant `1 / ln(2)` with the ROM's `fmul`, giving a
+    /// slightly larger error than `fln` alone.  Negative inputs return NaN, and zero returns
+    /// negative infinity.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn float_log2(x: f32) -> f32 {
+        Self::sf_binary(SF_TABLE_FMUL, Self::fln(x), core::f32::consts::LOG2_E)
+    }
+
+    /// Returns 10 raised to the power `x`.
+    ///
+    /// The ROM has no `exp10`, so this is the ROM's `fexp` of `x * ln(10)`,
+    /// multiplied with the ROM's `fmul`.  The rounding error of the product grows with `x`, so the relative
+    /// error is larger than `fexp`'s for large `x`.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn float_exp10(x: f32) -> f32 {
+        Self::fexp(Self::sf_binary(SF_TABLE_FMUL, x, core::f32::consts::LN_10))
+    }
+}
+
+/// Public double precision soft-float functions
+#[cfg(feature = "soft-double")]
+impl ROM {
+    /// Converts a double to a signed 64-bit integer.
+    ///
+    /// As with [`ROM::float_to_int`], the ROM rounds towards negative
+    /// infinity, so `-1.5` converts to `-2`.  Out of range values are clamped
+    /// to `i64::MIN` and `i64::MAX`.
+    ///
+    /// # Returns
+    ///
+    /// The converted value, or [`RomError::UnsupportedRomVersion`] on a B0
+    /// (version 1) ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_to_int(x: f64) -> Result<i64, RomError> {
+        type RomDouble2Int64Fn = unsafe extern "C" fn(x: f64) -> i64;
+
+        let func: RomDouble2Int64Fn =
+            core::mem::transmute(Self::sd_table_func(SD_TABLE_DOUBLE2INT64)?);
+        Ok(func(x))
+    }
+
+    /// Converts a signed 64-bit integer to a double, rounding to the nearest
+    /// representable value.
+    ///
+    /// # Returns
+    ///
+    /// The converted value, or [`RomError::UnsupportedRomVersion`] on a B0
+    /// (version 1) ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn int_to_double(x: i64) -> Result<f64, RomError> {
+        type RomInt642DoubleFn = unsafe extern "C" fn(x: i64) -> f64;
+
+        let func: RomInt642DoubleFn =
+            core::mem::transmute(Self::sd_table_func(SD_TABLE_INT642DOUBLE)?);
+        Ok(func(x))
+    }
+
+    /// Converts an unsigned 32-bit integer to a double, which is exact.
+    ///
+    /// # Returns
+    ///
+    /// The converted value, or [`RomError::UnsupportedRomVersion`] on a B0
+    /// (version 1) ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn uint_to_double(x: u32) -> Result<f64, RomError> {
+        type RomUint2DoubleFn = unsafe extern "C" fn(x: u32) -> f64;
+
+        let func: RomUint2DoubleFn =
+            core::mem::transmute(Self::sd_table_func(SD_TABLE_UINT2DOUBLE)?);
+        Ok(func(x))
+    }
+
+    /// Returns the absolute value of `d`, by clearing the sign bit.
+    ///
+    /// The ROM has no absolute value function, as none is needed - this is
+    /// a single bit operation, so never calls a soft-float routine.  `-0.0`
+    /// becomes `0.0`, and NaNs keep their payload, with the sign cleared.
+    pub fn double_abs(d: f64) -> f64 {
+        f64::from_bits(d.to_bits() & !DOUBLE_SIGN_BIT)
+    }
+
+    /// Returns `d` negated, by flipping the sign bit.
+    ///
+    /// As with [`ROM::double_abs`], this never calls a soft-float routine.
+    /// `0.0` becomes `-0.0`, and NaNs keep their payload, with the sign
+    /// flipped.
+    pub fn double_neg(d: f64) -> f64 {
+        f64::from_bits(d.to_bits() ^ DOUBLE_SIGN_BIT)
+    }
+
+    /// Compares two doubles.
+    ///
+    /// As with [`ROM::fcmp`], the ROM comparison has no "unordered" result,
+    /// so the result is not meaningful if either operand is NaN - check with
+    /// `is_nan()` first if NaNs are possible.  As denormals are flushed to
+    /// zero, they compare equal to zero.
+    ///
+    /// # Returns
+    ///
+    /// The ordering of `a` relative to `b`, or
+    /// [`RomError::UnsupportedRomVersion`] on a B0 (version 1) ROM, which has
+    /// no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn dcmp(a: f64, b: f64) -> Result<Ordering, RomError> {
+        type RomDcmpFn = unsafe extern "C" fn(a: f64, b: f64) -> i32;
+
+        let func: RomDcmpFn = core::mem::transmute(Self::sd_table_func(SD_TABLE_DCMP)?);
+        Ok(func(a, b).cmp(&0))
+    }
+
+    /// Returns the four quadrant arctangent of `y` and `x`, in radians, as
+    /// C99 `atan2`, using the ROM's `datan2`.
+    ///
+    /// The result has the sign of `y`, and is in the right half plane
+    /// (-pi/2 to pi/2) when `x` is positive, or the left when it is
+    /// negative.  With both arguments zero, C99 gives 0 when `x` is +0 and
+    /// pi when `x` is -0, signed as `y`.  As the ROM flushes denormals to
+    /// zero, denormal arguments are treated as zeros.
+    ///
+    /// # Returns
+    ///
+    /// The angle, in the range -pi to pi, or
+    /// [`RomError::UnsupportedRomVersion`] on a B0 (version 1) ROM, which
+    /// has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_atan2(y: f64, x: f64) -> Result<f64, RomError> {
+        type RomDatan2Fn = unsafe extern "C" fn(y: f64, x: f64) -> f64;
+
+        let func: RomDatan2Fn = core::mem::transmute(Self::sd_table_func(SD_TABLE_DATAN2)?);
+        Ok(func(y, x))
+    }
+
+    /// Returns the base 10 logarithm of `x`, as [`ROM::float_log10`], using
+    /// the ROM's `dln` and `dmul`.
+    ///
+    /// # Returns
+    ///
+    /// The logarithm, or [`RomError::UnsupportedRomVersion`] on a B0
+    /// (version 1) ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_log10(x: f64) -> Result<f64, RomError> {
+        Self::sd_binary(
+            SD_TABLE_DMUL,
+            Self::sd_unary(SD_TABLE_DLN, x)?,
+            core::f64::consts::LOG10_E,
+        )
+    }
+
+    /// Returns the base 2 logarithm of `x`, as [`ROM::float_log2`], using
+    /// the ROM's `dln` and `dmul`.
+    ///
+    /// # Returns
+    ///
+    /// The logarithm, or [`RomError::UnsupportedRomVersion`] on a B0
+    /// (version 1) ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_log2(x: f64) -> Result<f64, RomError> {
+        Self::sd_binary(
+            SD_TABLE_DMUL,
+            Self::sd_unary(SD_TABLE_DLN, x)?,
+            core::f64::consts::LOG2_E,
+        )
+    }
+
+    /// Returns 10 raised to the power `x`, as [`ROM::float_exp10`], using
+    /// the ROM's `dexp` and `dmul`.
+    ///
+    /// # Returns
+    ///
+    /// The power, or [`RomError::UnsupportedRomVersion`] on a B0 (version 1)
+    /// ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_exp10(x: f64) -> Result<f64, RomError> {
+        let x = Self::sd_binary(SD_TABLE_DMUL, x, core::f64::consts::LN_10)?;
+        Self::sd_unary(SD_TABLE_DEXP, x)
+    }
+
+    /// Divides `a` by `b`, using the ROM's `ddiv`, unless `b` is zero, as
+    /// [`ROM::float_div_checked`].
+    ///
+    /// # Returns
+    ///
+    /// The quotient, `None` if `b` is zero or denormal, or
+    /// [`RomError::UnsupportedRomVersion`] on a B0 (version 1) ROM, which
+    /// has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_div_checked(a: f64, b: f64) -> Result<Option<f64>, RomError> {
+        type RomDdivFn = unsafe extern "C" fn(a: f64, b: f64) -> f64;
+
+        let func: RomDdivFn = core::mem::transmute(Self::sd_table_func(SD_TABLE_DDIV)?);
+        if b == 0.0 || b.is_subnormal() {
+            return Ok(None);
+        }
+
+        Ok(Some(func(a, b)))
+    }
+
+    /// Converts a double to a signed 32-bit fixed point value with `n`
+    /// fractional bits, rounding towards zero (truncating).
+    ///
+    /// The ROM has a single conversion, `double2fix` (offset 0x20 in the
+    /// `SD` table), which rounds towards negative infinity, like
+    /// [`ROM::double_to_int`] - there are no separate round to zero or
+    /// round to nearest entries.  So for negative values which aren't exact
+    /// this adds one to the ROM's result, checked by converting back with
+    /// `fix2double` (offset 0x30).  Out of range values are clamped to
+    /// `i32::MIN` and `i32::MAX`.
+    ///
+    /// Truncation biases results towards zero, so prefer
+    /// [`ROM::double_to_fix_rn`] where that matters, such as in DSP code.
+    ///
+    /// ```rust,ignore
+    /// assert_eq!(ROM::double_to_fix_rz(-2.5, 0), Ok(-2));
+    /// assert_eq!(ROM::double_to_fix_rz(0.75, 1), Ok(1));
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The converted value, or [`RomError::UnsupportedRomVersion`] on a B0
+    /// (version 1) ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_to_fix_rz(d: f64, n: i32) -> Result<i32, RomError> {
+        type RomFix2DoubleFn = unsafe extern "C" fn(a: i32, n: i32) -> f64;
+
+        let floor = Self::double_to_fix_floor(d, n)?;
+        let fix2double: RomFix2DoubleFn =
+            core::mem::transmute(Self::sd_table_func(SD_TABLE_FIX2DOUBLE)?);
+        Ok(fix_floor_to_zero(d, floor, fix2double(floor, n)))
+    }
+
+    /// Converts a double to a signed 32-bit fixed point value with `n`
+    /// fractional bits, rounding to the nearest value, with halves rounded
+    /// up (round half up, towards positive infinity, so -2.5 gives -2).
+    ///
+    /// Implemented by converting with the ROM's `double2fix`, which rounds
+    /// towards negative infinity - see [`ROM::double_to_fix_rz`] - then
+    /// rounding up if `d` is at or above the midpoint between the result and
+    /// the next value.  The midpoint is the result plus half of the least
+    /// significant fixed point bit, `2^-(n+1)`, with the ROM's `fix2double`
+    /// and `dadd`, and is compared with `dcmp`, so no native floating point
+    /// arithmetic is used.  Out of range values are clamped to
+    /// `i32::MIN` and `i32::MAX`.  For `n` outside `-1024..=1073`, half the
+    /// least significant bit isn't representable as a double, so is clamped
+    /// to the nearest value which is, and halves may not round exactly.
+    ///
+    /// ```rust,ignore
+    /// assert_eq!(ROM::double_to_fix_rn(2.5, 0), Ok(3));
+    /// assert_eq!(ROM::double_to_fix_rn(-2.5, 0), Ok(-2));
+    /// assert_eq!(ROM::double_to_fix_rn(-2.6, 0), Ok(-3));
+    /// ```
+    ///
+    /// # Returns
+    ///
+    /// The converted value, or [`RomError::UnsupportedRomVersion`] on a B0
+    /// (version 1) ROM, which has no double precision table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn double_to_fix_rn(d: f64, n: i32) -> Result<i32, RomError> {
+        type RomDouble2FixFn = unsafe extern "C" fn(a: f64, n: i32) -> i32;
+        type RomFix2DoubleFn = unsafe extern "C" fn(a: i32, n: i32) -> f64;
+        type RomDaddFn = unsafe extern "C" fn(a: f64, b: f64) -> f64;
+        type RomDcmpFn = unsafe extern "C" fn(a: f64, b: f64) -> i32;
+
+        let double2fix: RomDouble2FixFn =
+            core::mem::transmute(Self::sd_table_func(SD_TABLE_DOUBLE2FIX)?);
+        let fix2double: RomFix2DoubleFn =
+            core::mem::transmute(Self::sd_table_func(SD_TABLE_FIX2DOUBLE)?);
+        let dadd: RomDaddFn = core::mem::transmute(Self::sd_table_func(SD_TABLE_DADD)?);
+        let dcmp: RomDcmpFn = core::mem::transmute(Self::sd_table_func(SD_TABLE_DCMP)?);
+
+        Ok(fix_round_half_up(
+            d,
+            n,
+            |d, n| double2fix(d, n),
+            |floor, n| dadd(fix2double(floor, n), fix_half_lsb(n)),
+            |a, b| dcmp(a, b).cmp(&0),
+        ))
+    }
+}
+
+// Private soft-float functions
+impl ROM {
+    // Get a function pointer from the soft-float table.  Only for functions
+    // present in the version 1 table, which every RP2040 ROM has.
+    unsafe fn sf_table_func(offset: usize) -> *const core::ffi::c_void {
+        let table =
+            Self::rom_data_lookup(rom_codes::SOFT_FLOAT_TABLE) as *const *const core::ffi::c_void;
+        *table.add(offset / 4)
+    }
+
+    // Call a single argument function from the soft-float table
+    pub(crate) unsafe fn sf_unary(offset: usize, x: f32) -> f32 {
+        type RomFloatUnaryFn = unsafe extern "C" fn(x: f32) -> f32;
+
+        let func: RomFloatUnaryFn = core::mem::transmute(Self::sf_table_func(offset));
+        func(x)
+    }
+
+    // Call a two argument function from the soft-float table
+    pub(crate) unsafe fn sf_binary(offset: usize, a: f32, b: f32) -> f32 {
+        type RomFloatBinaryFn = unsafe extern "C" fn(a: f32, b: f32) -> f32;
+
+        let func: RomFloatBinaryFn = core::mem::transmute(Self::sf_table_func(offset));
+        func(a, b)
+    }
+
+    // Get a function pointer from the soft-float table, for functions added
+    // in version 2
+    unsafe fn sf_table_func_v2(offset: usize) -> Result<*const core::ffi::c_void, RomError> {
+        let version = Self::rom_version();
+        if offset >= SF_TABLE_V1_SIZE && version < 2 {
+            return Err(RomError::UnsupportedRomVersion { version });
+        }
+
+        Ok(Self::sf_table_func(offset))
+    }
+
+    // Get a function pointer from the double precision soft-float table,
+    // which only version 2 ROMs and later have
+    #[cfg(feature = "soft-double")]
+    unsafe fn sd_table_func(offset: usize) -> Result<*const core::ffi::c_void, RomError> {
+        let version = Self::rom_version();
+        if version < 2 {
+            return Err(RomError::UnsupportedRomVersion { version });
+        }
+
+        let table =
+            Self::rom_data_lookup(rom_codes::SOFT_DOUBLE_TABLE) as *const *const core::ffi::c_void;
+        Ok(*table.add(offset / 4))
+    }
+
+    // Call a single argument function from the double precision soft-float
+    // table
+    #[cfg(feature = "soft-double")]
+    unsafe fn sd_unary(offset: usize, x: f64) -> Result<f64, RomError> {
+        type RomDoubleUnaryFn = unsafe extern "C" fn(x: f64) -> f64;
+
+        let func: RomDoubleUnaryFn = core::mem::transmute(Self::sd_table_func(offset)?);
+        Ok(func(x))
+    }
+
+    // Call a two argument function from the double precision soft-float
+    // table
+    #[cfg(feature = "soft-double")]
+    unsafe fn sd_binary(offset: usize, a: f64, b: f64) -> Result<f64, RomError> {
+        type RomDoubleBinaryFn = unsafe extern "C" fn(a: f64, b: f64) -> f64;
+
+        let func: RomDoubleBinaryFn = core::mem::transmute(Self::sd_table_func(offset)?);
+        Ok(func(a, b))
+    }
+
+    // Convert a double to fixed point using the ROM's double2fix, which
+    // rounds towards negative infinity
+    #[cfg(feature = "soft-double")]
+    unsafe fn double_to_fix_floor(d: f64, n: i32) -> Result<i32, RomError> {
+        type RomDouble2FixFn = unsafe extern "C" fn(a: f64, n: i32) -> i32;
+
+        let func: RomDouble2FixFn = core::mem::transmute(Self::sd_table_func(SD_TABLE_DOUBLE2FIX)?);
+        Ok(func(d, n))
+    }
+}
+
+// Turn a fixed point value rounded towards negative infinity into one
+// rounded towards zero.  `floor_value` is `floor` converted back to a double,
+// which is below `d` if the conversion wasn't exact, but above it if `floor`
+// was clamped at i32::MIN.
+#[cfg(feature = "soft-double")]
+fn fix_floor_to_zero(d: f64, floor: i32, floor_value: f64) -> i32 {
+    if d < 0.0 && floor_value < d {
+        floor + 1
+    } else {
+        floor
+    }
+}
+
+// Round a double to fixed point with `n` fractional bits, to the nearest
+// value with halves rounded up.  `floor` converts rounding towards negative
+// infinity, such as the ROM's double2fix, `midpoint` returns the value
+// halfway between a fixed point value and the next, and `cmp` compares two
+// doubles, so each can be a ROM function.  Comparing against the midpoint,
+// rather than adding half and converting, can't be thrown off by the add
+// rounding up into the next binade.
+#[cfg(feature = "soft-double")]
+fn fix_round_half_up(
+    d: f64,
+    n: i32,
+    floor: impl FnOnce(f64, i32) -> i32,
+    midpoint: impl FnOnce(i32, i32) -> f64,
+    cmp: impl FnOnce(f64, f64) -> Ordering,
+) -> i32 {
+    let floor = floor(d, n);
+    if cmp(d, midpoint(floor, n)) == Ordering::Less {
+        floor
+    } else {
+        floor.saturating_add(1)
+    }
+}
+
+// Half the least significant bit of a fixed point value with `n` fractional
+// bits, 2^-(n+1), built from its bits, as core has no powi.  Clamped to zero
+// below the smallest subnormal, and to 2^1023 above.
+#[cfg(feature = "soft-double")]
+fn fix_half_lsb(n: i32) -> f64 {
+    let exp = -(n as i64) - 1;
+    if exp < -1074 {
+        0.0
+    } else if exp < -1022 {
+        f64::from_bits(1 << (exp + 1074))
+    } else {
+        f64::from_bits(((exp.min(1023) + 1023) as u64) << 52)
+    }
+}
+
+/// Public soft-float functions
+impl RomMath {
+    /// See [`ROM::float_to_int`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::float_to_int`].
+    #[inline]
+    pub unsafe fn float_to_int(x: f32) -> i32 {
+        ROM::float_to_int(x)
+    }
+
+    /// See [`ROM::int_to_float`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::int_to_float`].
+    #[inline]
+    pub unsafe fn int_to_float(x: i32) -> f32 {
+        ROM::int_to_float(x)
+    }
+
+    /// See [`ROM::uint_to_float`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::uint_to_float`].
+    #[inline]
+    pub unsafe fn uint_to_float(x: u32) -> f32 {
+        ROM::uint_to_float(x)
+    }
+
+    /// See [`ROM::fcmp`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::fcmp`].
+    #[inline]
+    pub unsafe fn fcmp(a: f32, b: f32) -> Result<Ordering, RomError> {
+        ROM::fcmp(a, b)
+    }
+
+    /// See [`ROM::fsin`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::fsin`].
+    #[inline]
+    pub unsafe fn fsin(x: f32) -> f32 {
+        ROM::fsin(x)
+    }
+
+    /// See [`ROM::fcos`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::fcos`].
+    #[inline]
+    pub unsafe fn fcos(x: f32) -> f32 {
+        ROM::fcos(x)
+    }
+
+    /// See [`ROM::ftan`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::ftan`].
+    #[inline]
+    pub unsafe fn ftan(x: f32) -> f32 {
+        ROM::ftan(x)
+    }
+
+    /// See [`ROM::fexp`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::fexp`].
+    #[inline]
+    pub unsafe fn fexp(x: f32) -> f32 {
+        ROM::fexp(x)
+    }
+
+    /// See [`ROM::fln`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::fln`].
+    #[inline]
+    pub unsafe fn fln(x: f32) -> f32 {
+        ROM::fln(x)
+    }
+
+    /// See [`ROM::fatan2`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::fatan2`].
+    #[inline]
+    pub unsafe fn fatan2(y: f32, x: f32) -> Result<f32, RomError> {
+        ROM::fatan2(y, x)
+    }
+
+    /// See [`ROM::float_div_checked`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::float_div_checked`].
+    #[inline]
+    pub unsafe fn float_div_checked(a: f32, b: f32) -> Option<f32> {
+        ROM::float_div_checked(a, b)
+    }
+
+    /// See [`ROM::float_log10`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::float_log10`].
+    #[inline]
+    pub unsafe fn float_log10(x: f32) -> f32 {
+        ROM::float_log10(x)
+    }
+
+    /// See [`ROM::float_log2`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::float_log2`].
+    #[inline]
+    pub unsafe fn float_log2(x: f32) -> f32 {
+        ROM::float_log2(x)
+    }
+
+    /// See [`ROM::float_exp10`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::float_exp10`].
+    #[inline]
+    pub unsafe fn float_exp10(x: f32) -> f32 {
+        ROM::float_exp10(x)
+    }
+}
+
+/// Public double precision soft-float functions
+#[cfg(feature = "soft-double")]
+impl RomMath {
+    /// See [`ROM::double_to_int`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_to_int`].
+    #[inline]
+    pub unsafe fn double_to_int(x: f64) -> Result<i64, RomError> {
+        ROM::double_to_int(x)
+    }
+
+    /// See [`ROM::int_to_double`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::int_to_double`].
+    #[inline]
+    pub unsafe fn int_to_double(x: i64) -> Result<f64, RomError> {
+        ROM::int_to_double(x)
+    }
+
+    /// See [`ROM::uint_to_double`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::uint_to_double`].
+    #[inline]
+    pub unsafe fn uint_to_double(x: u32) -> Result<f64, RomError> {
+        ROM::uint_to_double(x)
+    }
+
+    /// See [`ROM::double_abs`].
+    #[inline]
+    pub fn double_abs(d: f64) -> f64 {
+        ROM::double_abs(d)
+    }
+
+    /// See [`ROM::double_neg`].
+    #[inline]
+    pub fn double_neg(d: f64) -> f64 {
+        ROM::double_neg(d)
+    }
+
+    /// See [`ROM::dcmp`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::dcmp`].
+    #[inline]
+    pub unsafe fn dcmp(a: f64, b: f64) -> Result<Ordering, RomError> {
+        ROM::dcmp(a, b)
+    }
+
+    /// See [`ROM::double_atan2`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_atan2`].
+    #[inline]
+    pub unsafe fn double_atan2(y: f64, x: f64) -> Result<f64, RomError> {
+        ROM::double_atan2(y, x)
+    }
+
+    /// See [`ROM::double_log10`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_log10`].
+    #[inline]
+    pub unsafe fn double_log10(x: f64) -> Result<f64, RomError> {
+        ROM::double_log10(x)
+    }
+
+    /// See [`ROM::double_log2`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_log2`].
+    #[inline]
+    pub unsafe fn double_log2(x: f64) -> Result<f64, RomError> {
+        ROM::double_log2(x)
+    }
+
+    /// See [`ROM::double_exp10`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_exp10`].
+    #[inline]
+    pub unsafe fn double_exp10(x: f64) -> Result<f64, RomError> {
+        ROM::double_exp10(x)
+    }
+
+    /// See [`ROM::double_div_checked`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_div_checked`].
+    #[inline]
+    pub unsafe fn double_div_checked(a: f64, b: f64) -> Result<Option<f64>, RomError> {
+        ROM::double_div_checked(a, b)
+    }
+
+    /// See [`ROM::double_to_fix_rz`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_to_fix_rz`].
+    #[inline]
+    pub unsafe fn double_to_fix_rz(d: f64, n: i32) -> Result<i32, RomError> {
+        ROM::double_to_fix_rz(d, n)
+    }
+
+    /// See [`ROM::double_to_fix_rn`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::double_to_fix_rn`].
+    #[inline]
+    pub unsafe fn double_to_fix_rn(d: f64, n: i32) -> Result<i32, RomError> {
+        ROM::double_to_fix_rn(d, n)
+    }
+}
+
+#[cfg(all(test, feature = "soft-double"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fix_floor_to_zero_at_half() {
+        // double2fix(2.5, 0) and double2fix(-2.5, 0) give 2 and -3
+        assert_eq!(fix_floor_to_zero(2.5, 2, 2.0), 2);
+        assert_eq!(fix_floor_to_zero(-2.5, -3, -3.0), -2);
+
+        // Exact values, and values clamped at i32::MIN, are unchanged
+        assert_eq!(fix_floor_to_zero(-3.0, -3, -3.0), -3);
+        assert_eq!(
+            fix_floor_to_zero(-1e12, i32::MIN, i32::MIN as f64),
+            i32::MIN
+        );
+    }
+
+    // A model of double2fix, for 0 or 1 fractional bits: rounds towards
+    // negative infinity, clamping to the i32 range
+    fn floor_model(d: f64, n: i32) -> i32 {
+        let scaled = d * if n == 0 { 1.0 } else { 2.0 };
+        let t = scaled as i64;
+        let floor = if (t as f64) > scaled { t - 1 } else { t };
+        floor.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+    }
+
+    // fix_round_half_up, with double2fix, the midpoint and dcmp modelled on
+    // the host
+    fn round_model(d: f64, n: i32) -> i32 {
+        fix_round_half_up(
+            d,
+            n,
+            floor_model,
+            |floor, n| floor as f64 / if n == 0 { 1.0 } else { 2.0 } + fix_half_lsb(n),
+            |a, b| a.partial_cmp(&b).unwrap(),
+        )
+    }
+
+    #[test]
+    fn fix_round_half_up_at_half() {
+        // Halves round towards positive infinity, whatever the sign
+        assert_eq!(round_model(2.5, 0), 3);
+        assert_eq!(round_model(-2.5, 0), -2);
+        assert_eq!(round_model(1.25, 1), 3);
+        assert_eq!(round_model(-1.25, 1), -2);
+
+        // Otherwise to nearest, including just below a half, where adding
+        // 0.5 would round up to 1.0
+        assert_eq!(round_model(2.4, 0), 2);
+        assert_eq!(round_model(-2.6, 0), -3);
+        assert_eq!(round_model(-0.4, 0), 0);
+        assert_eq!(round_model(0.49999999999999994, 0), 0);
+        assert_eq!(round_model(-0.5000000000000001, 0), -1);
+
+        // Out of range values saturate
+        assert_eq!(round_model(1e12, 0), i32::MAX);
+        assert_eq!(round_model(-1e12, 0), i32::MIN);
+        assert_eq!(round_model(i32::MAX as f64, 0), i32::MAX);
+    }
+
+    #[test]
+    fn fix_rn_and_rz_differ_at_half() {
+        // double_to_fix_rn rounds 2.5 up, double_to_fix_rz truncates it
+        let rz = |d: f64| fix_floor_to_zero(d, floor_model(d, 0), floor_model(d, 0) as f64);
+        assert_eq!((round_model(2.5, 0), rz(2.5)), (3, 2));
+        assert_eq!((round_model(-2.5, 0), rz(-2.5)), (-2, -2));
+        assert_eq!((round_model(-3.5, 0), rz(-3.5)), (-3, -3));
+        assert_eq!((round_model(-2.6, 0), rz(-2.6)), (-3, -2));
+    }
+
+    #[test]
+    fn fix_half_lsb_is_clamped() {
+        assert_eq!(fix_half_lsb(0), 0.5);
+        assert_eq!(fix_half_lsb(1), 0.25);
+        assert_eq!(fix_half_lsb(-1), 1.0);
+        assert_eq!(fix_half_lsb(-3), 4.0);
+        assert_eq!(fix_half_lsb(1073), f64::from_bits(1));
+        assert_eq!(fix_half_lsb(1074), 0.0);
+        assert_eq!(fix_half_lsb(i32::MAX), 0.0);
+        assert_eq!(fix_half_lsb(-1024), f64::from_bits(2046 << 52));
+        assert_eq!(fix_half_lsb(i32::MIN), f64::from_bits(2046 << 52));
+    }
+
+    #[test]
+    fn double_abs_and_neg() {
+        assert_eq!(ROM::double_abs(-0.0).to_bits(), 0.0f64.to_bits());
+        assert_eq!(ROM::double_abs(-2.5), 2.5);
+        assert!(ROM::double_abs(f64::NAN).is_nan());
+        assert!(ROM::double_abs(-f64::NAN).is_sign_positive());
+
+        assert_eq!(ROM::double_neg(f64::INFINITY), f64::NEG_INFINITY);
+        assert_eq!(ROM::double_neg(0.0).to_bits(), (-0.0f64).to_bits());
+        assert_eq!(ROM::double_neg(-2.5), 2.5);
+    }
+}
@@ -0,0 +1,71 @@
+//! The signatures of the ROM functions this crate wraps, for calling them
+//! via [`ROM::rom_func_lookup`](crate::ROM::rom_func_lookup) or
+//! [`RomFunc`](crate::RomFunc).
+//!
+//! Each is named after the ROM function, and documented with its two
+//! character code.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+/// `reset_usb_boot` - `UB`.  RP2040 only.
+pub type RomResetUsbBootFn =
+    unsafe extern "C" fn(usb_activity_gpio_pin_mask: u32, disable_interface_mask: u32);
+
+/// `reboot` - `RB`.  RP2350 only.
+pub type RomRebootFn = unsafe extern "C" fn(flags: u32, delay_ms: u32, p0: u32, p1: u32) -> i32;
+
+/// `rom_table_lookup`, found via the ROM header rather than a code.  RP2040
+/// signature.
+#[cfg(not(feature = "rp2350"))]
+pub type RomTableLookupFn =
+    unsafe extern "C" fn(table: *const u16, code: u32) -> *mut core::ffi::c_void;
+
+/// `rom_table_lookup`, found via the ROM header rather than a code.  RP2350
+/// signature.
+#[cfg(feature = "rp2350")]
+pub type RomTableLookupFn = unsafe extern "C" fn(code: u32, flags: u32) -> *mut core::ffi::c_void;
+
+/// `popcount32` - `P3`
+pub type RomPopcount32Fn = unsafe extern "C" fn(value: u32) -> u32;
+
+/// `reverse32` - `R3`
+pub type RomReverse32Fn = unsafe extern "C" fn(value: u32) -> u32;
+
+/// `clz32` - `L3`
+pub type RomClz32Fn = unsafe extern "C" fn(value: u32) -> u32;
+
+/// `ctz32` - `T3`
+pub type RomCtz32Fn = unsafe extern "C" fn(value: u32) -> u32;
+
+/// `memset` - `MS`
+pub type RomMemsetFn = unsafe extern "C" fn(ptr: *mut u8, c: u8, n: u32) -> *mut u8;
+
+/// `memset4` - `S4`
+pub type RomMemset4Fn = unsafe extern "C" fn(ptr: *mut u32, c: u8, n: u32) -> *mut u32;
+
+/// `memcpy` - `MC`
+pub type RomMemcpyFn = unsafe extern "C" fn(dest: *mut u8, src: *const u8, n: u32) -> *mut u8;
+
+/// `memcpy44` - `C4`
+pub type RomMemcpy44Fn = unsafe extern "C" fn(dest: *mut u32, src: *const u32, n: u32) -> *mut u32;
+
+/// `connect_internal_flash` - `IF`
+pub type RomConnectInternalFlashFn = unsafe extern "C" fn();
+
+/// `flash_exit_xip` - `EX`
+pub type RomFlashExitXipFn = unsafe extern "C" fn();
+
+/// `flash_range_erase` - `RE`
+pub type RomFlashRangeEraseFn =
+    unsafe extern "C" fn(addr: u32, count: usize, block_size: u32, block_cmd: u8);
+
+/// `flash_range_program` - `RP`
+pub type RomFlashRangeProgramFn = unsafe extern "C" fn(addr: u32, data: *const u8, count: usize);
+
+/// `flash_flush_cache` - `FC`
+pub type RomFlashFlushCacheFn = unsafe extern "C" fn();
+
+/// `flash_enter_cmd_xip` - `CX`
+pub type RomFlashEnterCmdXipFn = unsafe extern "C" fn();
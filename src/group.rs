@@ -0,0 +1,955 @@
+//! Groups of ROM functions, looked up together.
+//!
+//! Each group looks up all of its functions in a single pass, and stores the
+//! typed function pointers, so calls go straight to the ROM without walking
+//! the ROM table each time.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+#[cfg(feature = "flash-ops")]
+use crate::flash::{
+    cache_flush_barrier, debug_assert_interrupts_disabled, in_xip_flash, FlashAddr, FlashError,
+    FlashLayout, FlashSize, PageBuffer, FLASH_BLOCK_ERASE_CMD, FLASH_BLOCK_SIZE, FLASH_PAGE_SIZE,
+    FLASH_SECTOR_SIZE,
+};
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+use core::cmp::Ordering;
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+use crate::float::{
+    SF_TABLE_FADD, SF_TABLE_FCMP, SF_TABLE_FCOS, SF_TABLE_FDIV, SF_TABLE_FEXP, SF_TABLE_FLN,
+    SF_TABLE_FLOAT2INT, SF_TABLE_FMUL, SF_TABLE_FSIN, SF_TABLE_FSQRT, SF_TABLE_FSUB, SF_TABLE_FTAN,
+    SF_TABLE_INT2FLOAT,
+};
+#[cfg(feature = "flash-ops")]
+use crate::fn_types::{RomFlashRangeEraseFn, RomFlashRangeProgramFn};
+#[cfg(feature = "mem-ops")]
+use crate::fn_types::{RomMemcpy44Fn, RomMemcpyFn, RomMemset4Fn, RomMemsetFn};
+#[cfg(any(
+    feature = "flash-ops",
+    feature = "bit-ops",
+    feature = "mem-ops",
+    all(feature = "soft-float", not(feature = "rp2350"))
+))]
+use crate::rom_codes;
+use crate::{RomError, ROM};
+
+/// A group of ROM functions which are looked up together.
+pub trait RomFunctionGroup: Sized {
+    /// Looks up every function in the group.
+    ///
+    /// # Returns
+    ///
+    /// The group, or [`RomError::FunctionNotFound`] for the first function
+    /// which isn't present in the ROM.
+    fn lookup() -> Result<Self, RomError>;
+}
+
+#[cfg_attr(
+    not(any(feature = "flash-ops", feature = "bit-ops", feature = "mem-ops")),
+    allow(dead_code)
+)]
+// Look up a function, converting it to its typed function pointer
+//
+// Safety: F must be the correct signature for the function with this code
+unsafe fn lookup<F: Copy>(code: (u8, u8)) -> Result<F, RomError> {
+    ROM::lookup_func::<F>(code)
+        .map(|func| func.get())
+        .ok_or(RomError::FunctionNotFound { code })
+}
+
+#[cfg(feature = "flash-ops")]
+type RomVoidFn = unsafe extern "C" fn();
+
+#[cfg(feature = "flash-ops")]
+/// The ROM flash functions.
+///
+/// Calls go through the stored function pointers, bypassing the ROM table
+/// walk, so this is the high performance path for code doing many flash
+/// operations.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rp2040_rom::{FlashAddr, FlashOps, FlashSize, RomFunctionGroup};
+///
+/// let flash_ops = FlashOps::lookup().unwrap();
+/// unsafe {
+///     flash_ops
+///         .erase(FlashAddr::new(0x10_0000), FlashSize::new(4096))
+///         .unwrap();
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FlashOps {
+    connect_internal_flash: RomVoidFn,
+    exit_xip: RomVoidFn,
+    range_erase: RomFlashRangeEraseFn,
+    range_program: RomFlashRangeProgramFn,
+    flush_cache: RomVoidFn,
+    enter_cmd_xip: RomVoidFn,
+    layout: Option<FlashLayout>,
+}
+
+#[cfg(feature = "flash-ops")]
+impl RomFunctionGroup for FlashOps {
+    fn lookup() -> Result<Self, RomError> {
+        // Safety: the types match the datasheet signatures
+        unsafe {
+            Ok(Self {
+                connect_internal_flash: lookup(rom_codes::CONNECT_INTERNAL_FLASH)?,
+                exit_xip: lookup(rom_codes::FLASH_EXIT_XIP)?,
+                range_erase: lookup(rom_codes::FLASH_RANGE_ERASE)?,
+                range_program: lookup(rom_codes::FLASH_RANGE_PROGRAM)?,
+                flush_cache: lookup(rom_codes::FLASH_FLUSH_CACHE)?,
+                enter_cmd_xip: lookup(rom_codes::FLASH_ENTER_CMD_XIP)?,
+                layout: None,
+            })
+        }
+    }
+}
+
+/// The flash function pointers returned by [`ROM::resolve_all_flash_funcs`]
+#[cfg(feature = "flash-ops")]
+pub type FlashRomFns = FlashOps;
+
+#[cfg(feature = "flash-ops")]
+impl ROM {
+    /// Resolves every flash function pointer, while XIP is still enabled.
+    ///
+    /// Once flash has left XIP mode, even looking up the next ROM function
+    /// faults if the lookup code runs from flash.  Resolving everything up
+    /// front means the erase and program sequences in [`FlashRomFns`] call
+    /// through the stored pointers, with no table walk and no execution from
+    /// flash.  Equivalent to [`FlashOps::lookup`].
+    ///
+    /// # Returns
+    ///
+    /// The function pointers, or [`RomError::FunctionNotFound`] for the first
+    /// flash function which isn't present in the ROM.
+    pub fn resolve_all_flash_funcs() -> Result<FlashRomFns, RomError> {
+        FlashOps::lookup()
+    }
+}
+
+#[cfg(feature = "flash-ops")]
+impl FlashOps {
+    /// Returns these functions with [`erase`](Self::erase) and
+    /// [`program`](Self::program) restricted to the writable regions of
+    /// `layout`.
+    pub const fn with_layout(mut self, layout: FlashLayout) -> Self {
+        self.layout = Some(layout);
+        self
+    }
+
+    /// Erases a range of flash, performing the complete sequence of exiting
+    /// XIP, erasing, flushing the cache and re-entering XIP, from RAM.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Address to erase from, a multiple of 4096
+    /// * `size` - Number of bytes to erase, a multiple of 4096
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `addr` or `size` isn't sector aligned,
+    /// or [`FlashError::OutOfBounds`] if the range isn't writable in the
+    /// [layout](Self::with_layout).
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.
+    pub unsafe fn erase(&self, addr: FlashAddr, size: FlashSize) -> Result<(), FlashError> {
+        if !addr.offset().is_multiple_of(FLASH_SECTOR_SIZE)
+            || !size.bytes().is_multiple_of(FLASH_SECTOR_SIZE)
+        {
+            return Err(FlashError::Unaligned);
+        }
+        self.check_layout(addr.offset(), size.bytes() as usize)?;
+
+        self.erase_ram(addr.offset(), size.bytes() as usize);
+        Ok(())
+    }
+
+    /// Programs a range of flash, performing the complete sequence of
+    /// exiting XIP, programming, flushing the cache and re-entering XIP,
+    /// from RAM.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Address to program from, a multiple of 256
+    /// * `data` - Data to program, a multiple of 256 bytes, which must not be
+    ///   in flash
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `addr` or the length of `data` isn't page
+    /// aligned, or [`FlashError::OutOfBounds`] if the range isn't writable
+    /// in the [layout](Self::with_layout).
+    ///
+    /// # Safety
+    ///
+    /// Interrupts must be disabled, and the other core must not be executing
+    /// from flash, for the duration of this call.
+    pub unsafe fn program(&self, addr: FlashAddr, data: &[u8]) -> Result<(), FlashError> {
+        if !addr.offset().is_multiple_of(FLASH_PAGE_SIZE)
+            || !data.len().is_multiple_of(FLASH_PAGE_SIZE as usize)
+        {
+            return Err(FlashError::Unaligned);
+        }
+        self.check_layout(addr.offset(), data.len())?;
+
+        self.program_ram(addr.offset(), data);
+        Ok(())
+    }
+
+    /// Programs a single page of flash from a [`PageBuffer`], as
+    /// [`program`](Self::program).
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `addr` isn't page aligned.
+    ///
+    /// # Safety
+    ///
+    /// As for [`program`](Self::program).
+    pub unsafe fn program_page(
+        &self,
+        addr: FlashAddr,
+        page: &PageBuffer,
+    ) -> Result<(), FlashError> {
+        self.program(addr, page.as_bytes())
+    }
+
+    /// Calls the ROM's `connect_internal_flash`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::connect_internal_flash`].
+    pub unsafe fn connect_internal_flash(&self) {
+        (self.connect_internal_flash)();
+    }
+
+    /// Calls the ROM's `flash_exit_xip`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_exit_xip`].
+    pub unsafe fn exit_xip(&self) {
+        (self.exit_xip)();
+    }
+
+    /// Calls the ROM's `flash_range_erase`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_range_erase`].
+    pub unsafe fn range_erase(&self, addr: u32, count: usize, block_size: u32, block_cmd: u8) {
+        (self.range_erase)(addr, count, block_size, block_cmd);
+    }
+
+    /// Calls the ROM's `flash_range_program`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_range_program`].
+    pub unsafe fn range_program(&self, addr: u32, data: *const u8, count: usize) {
+        (self.range_program)(addr, data, count);
+    }
+
+    /// Calls the ROM's `flash_flush_cache`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_flush_cache`].
+    pub unsafe fn flush_cache(&self) {
+        (self.flush_cache)();
+    }
+
+    /// Calls the ROM's `flash_enter_cmd_xip`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::flash_enter_cmd_xip`].
+    pub unsafe fn enter_cmd_xip(&self) {
+        (self.enter_cmd_xip)();
+    }
+
+    // Check a write against the layout, if there is one.  Runs before XIP
+    // is disabled, so may run from flash.
+    fn check_layout(&self, offset: u32, len: usize) -> Result<(), FlashError> {
+        match &self.layout {
+            Some(layout) => layout.check(offset, len),
+            None => Ok(()),
+        }
+    }
+
+    // The erase sequence, which runs from RAM as XIP is disabled part way
+    // through
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn erase_ram(&self, addr: u32, count: usize) {
+        debug_assert_interrupts_disabled();
+
+        (self.connect_internal_flash)();
+        (self.exit_xip)();
+        (self.range_erase)(addr, count, FLASH_BLOCK_SIZE, FLASH_BLOCK_ERASE_CMD);
+        (self.flush_cache)();
+        cache_flush_barrier();
+        (self.enter_cmd_xip)();
+    }
+
+    // The program sequence, which runs from RAM as XIP is disabled part way
+    // through
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn program_ram(&self, addr: u32, data: &[u8]) {
+        debug_assert_interrupts_disabled();
+
+        (self.connect_internal_flash)();
+        (self.exit_xip)();
+        (self.range_program)(addr, data.as_ptr(), data.len());
+        (self.flush_cache)();
+        cache_flush_barrier();
+        (self.enter_cmd_xip)();
+    }
+}
+
+#[cfg(feature = "flash-ops")]
+/// Access to flash with XIP disabled, restoring XIP when dropped.
+///
+/// [`FlashAccess::begin`] connects the flash and exits XIP, the methods erase
+/// and program flash, and dropping the guard (or calling
+/// [`finish`](FlashAccess::finish)) flushes the cache and re-enters XIP, so
+/// the teardown can't be forgotten.  Every ROM function pointer is resolved
+/// before XIP is disabled, and the guard's own code runs from RAM.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rp2040_rom::{FlashAccess, FlashAddr, FlashSize};
+///
+/// // The data must be in RAM - a constant such as `&[0; 256]` would be
+/// // placed in flash
+/// let mut page = [0u8; 256];
+/// page[0] = 0x42;
+///
+/// // Must be run from RAM, with interrupts disabled
+/// unsafe {
+///     let mut flash = FlashAccess::begin().unwrap();
+///     flash
+///         .erase(FlashAddr::new(0x10_0000), FlashSize::new(4096))
+///         .unwrap();
+///     flash.program(FlashAddr::new(0x10_0000), &page).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FlashAccess {
+    ops: FlashOps,
+}
+
+#[cfg(feature = "flash-ops")]
+impl FlashAccess {
+    /// Resolves the flash functions, connects the flash and exits XIP.
+    ///
+    /// Constructing the guard is the unsafe step, as `Drop` can't be.
+    ///
+    /// # Returns
+    ///
+    /// The guard, or [`RomError::FunctionNotFound`] for the first flash
+    /// function which isn't present in the ROM, in which case XIP is left
+    /// enabled.
+    ///
+    /// # Safety
+    ///
+    /// Until the guard is dropped, nothing may execute from flash.  So the
+    /// calling code must run from RAM, interrupts must be disabled, and the
+    /// other core must not be executing from flash.  A panic faults, as the
+    /// panic handler runs from flash before the guard can restore XIP.  Data
+    /// passed to [`program`](FlashAccess::program) must not be in flash,
+    /// that is in the XIP window, `0x1000_0000..0x1400_0000`, so not a
+    /// constant or `static` without `mut`.  The caller is also
+    /// responsible for not erasing or programming flash which holds code or
+    /// data still in use.  The guard doesn't check a [`FlashLayout`], as the
+    /// layout may itself be in flash, so check writes with
+    /// [`FlashLayout::check`] before calling this.
+    pub unsafe fn begin() -> Result<Self, RomError> {
+        debug_assert_interrupts_disabled();
+
+        let access = Self {
+            ops: FlashOps::lookup()?,
+        };
+        access.begin_ram();
+        Ok(access)
+    }
+
+    /// Erases a range of flash.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Address to erase from, a multiple of 4096
+    /// * `size` - Number of bytes to erase, a multiple of 4096
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `addr` or `size` isn't sector aligned.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub fn erase(&mut self, addr: FlashAddr, size: FlashSize) -> Result<(), FlashError> {
+        if !addr.offset().is_multiple_of(FLASH_SECTOR_SIZE)
+            || !size.bytes().is_multiple_of(FLASH_SECTOR_SIZE)
+        {
+            return Err(FlashError::Unaligned);
+        }
+
+        // Safety: begin() exited XIP, and the pointers came from the ROM
+        unsafe {
+            (self.ops.range_erase)(
+                addr.offset(),
+                size.bytes() as usize,
+                FLASH_BLOCK_SIZE,
+                FLASH_BLOCK_ERASE_CMD,
+            );
+        }
+        Ok(())
+    }
+
+    /// Programs a range of flash.
+    ///
+    /// # Parameters
+    ///
+    /// * `addr` - Address to program from, a multiple of 256
+    /// * `data` - Data to program, a multiple of 256 bytes, which must not be
+    ///   in flash
+    ///
+    /// # Returns
+    ///
+    /// [`FlashError::Unaligned`] if `addr` or the length of `data` isn't page
+    /// aligned.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, if `data` is in flash, after restoring XIP.
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    pub fn program(&mut self, addr: FlashAddr, data: &[u8]) -> Result<(), FlashError> {
+        if !addr.offset().is_multiple_of(FLASH_PAGE_SIZE)
+            || !data.len().is_multiple_of(FLASH_PAGE_SIZE as usize)
+        {
+            return Err(FlashError::Unaligned);
+        }
+
+        // Data in flash can't be read with XIP disabled.  The panic handler
+        // is in flash too, so XIP is restored before panicking.
+        if cfg!(debug_assertions) && in_xip_flash(data.as_ptr(), data.len()) {
+            // Safety: begin() exited XIP, and the pointers came from the ROM
+            unsafe { self.end_ram() };
+            debug_check!(
+                false,
+                "FlashAccess::program data at {:#x} is in flash",
+                data.as_ptr() as usize
+            );
+        }
+
+        // Safety: begin() exited XIP, and the pointers came from the ROM
+        unsafe {
+            (self.ops.range_program)(addr.offset(), data.as_ptr(), data.len());
+        }
+        Ok(())
+    }
+
+    /// Flushes the cache and re-enters XIP, ending flash access.  Equivalent
+    /// to dropping the guard.
+    pub fn finish(self) {}
+
+    // Connect the flash and exit XIP, from RAM
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn begin_ram(&self) {
+        (self.ops.connect_internal_flash)();
+        (self.ops.exit_xip)();
+    }
+
+    // Flush the cache and re-enter XIP, from RAM
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    unsafe fn end_ram(&self) {
+        (self.ops.flush_cache)();
+        cache_flush_barrier();
+        (self.ops.enter_cmd_xip)();
+    }
+}
+
+#[cfg(feature = "flash-ops")]
+impl Drop for FlashAccess {
+    #[inline(never)]
+    #[cfg_attr(
+        all(target_arch = "arm", target_os = "none"),
+        link_section = ".data.ram_func"
+    )]
+    fn drop(&mut self) {
+        // Safety: begin() exited XIP, and the pointers came from the ROM
+        unsafe { self.end_ram() };
+    }
+}
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+type RomFloatBinaryFn = unsafe extern "C" fn(f32, f32) -> f32;
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+type RomFloatUnaryFn = unsafe extern "C" fn(f32) -> f32;
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+type RomFloat2IntFn = unsafe extern "C" fn(f32) -> i32;
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+type RomInt2FloatFn = unsafe extern "C" fn(i32) -> f32;
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+type RomFcmpFn = unsafe extern "C" fn(f32, f32) -> i32;
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+/// The ROM single precision soft-float functions.
+///
+/// Calls go through the stored function pointers, bypassing the soft-float
+/// table lookup, so this is the high performance path for code doing
+/// floating point in a hot loop.  As with the [`ROM`] soft-float functions,
+/// denormals are flushed to zero.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rp2040_rom::{FloatOps, RomFunctionGroup};
+///
+/// let float_ops = FloatOps::lookup().unwrap();
+/// let (x, y) = (3.0, 4.0);
+/// let hypot = float_ops.sqrt(float_ops.add(float_ops.mul(x, x), float_ops.mul(y, y)));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct FloatOps {
+    add: RomFloatBinaryFn,
+    sub: RomFloatBinaryFn,
+    mul: RomFloatBinaryFn,
+    div: RomFloatBinaryFn,
+    sqrt: RomFloatUnaryFn,
+    float_to_int: RomFloat2IntFn,
+    int_to_float: RomInt2FloatFn,
+    cos: RomFloatUnaryFn,
+    sin: RomFloatUnaryFn,
+    tan: RomFloatUnaryFn,
+    exp: RomFloatUnaryFn,
+    ln: RomFloatUnaryFn,
+    cmp: Option<RomFcmpFn>,
+}
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+impl RomFunctionGroup for FloatOps {
+    fn lookup() -> Result<Self, RomError> {
+        // Safety: the types match the datasheet signatures, and the offsets
+        // are within the soft-float table for this ROM version
+        unsafe {
+            let table = ROM::rom_data_lookup(rom_codes::SOFT_FLOAT_TABLE) as *const *const ();
+            if table.is_null() {
+                return Err(RomError::FunctionNotFound {
+                    code: rom_codes::SOFT_FLOAT_TABLE,
+                });
+            }
+
+            let cmp = if ROM::rom_version() >= 2 {
+                Some(sf_table_func(table, SF_TABLE_FCMP))
+            } else {
+                None
+            };
+
+            Ok(Self {
+                add: sf_table_func(table, SF_TABLE_FADD),
+                sub: sf_table_func(table, SF_TABLE_FSUB),
+                mul: sf_table_func(table, SF_TABLE_FMUL),
+                div: sf_table_func(table, SF_TABLE_FDIV),
+                sqrt: sf_table_func(table, SF_TABLE_FSQRT),
+                float_to_int: sf_table_func(table, SF_TABLE_FLOAT2INT),
+                int_to_float: sf_table_func(table, SF_TABLE_INT2FLOAT),
+                cos: sf_table_func(table, SF_TABLE_FCOS),
+                sin: sf_table_func(table, SF_TABLE_FSIN),
+                tan: sf_table_func(table, SF_TABLE_FTAN),
+                exp: sf_table_func(table, SF_TABLE_FEXP),
+                ln: sf_table_func(table, SF_TABLE_FLN),
+                cmp,
+            })
+        }
+    }
+}
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+impl FloatOps {
+    /// Returns `a + b`.
+    pub fn add(&self, a: f32, b: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.add)(a, b) }
+    }
+
+    /// Returns `a - b`.
+    pub fn sub(&self, a: f32, b: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.sub)(a, b) }
+    }
+
+    /// Returns `a * b`.
+    pub fn mul(&self, a: f32, b: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.mul)(a, b) }
+    }
+
+    /// Returns `a / b`.
+    pub fn div(&self, a: f32, b: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.div)(a, b) }
+    }
+
+    /// Returns the square root of `x`.
+    pub fn sqrt(&self, x: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.sqrt)(x) }
+    }
+
+    /// Converts a float to a signed integer, rounding towards negative
+    /// infinity, as [`ROM::float_to_int`].
+    pub fn float_to_int(&self, x: f32) -> i32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.float_to_int)(x) }
+    }
+
+    /// Converts a signed integer to a float, as [`ROM::int_to_float`].
+    pub fn int_to_float(&self, x: i32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.int_to_float)(x) }
+    }
+
+    /// Returns the cosine of `x`, in radians.
+    pub fn cos(&self, x: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.cos)(x) }
+    }
+
+    /// Returns the sine of `x`, in radians.
+    pub fn sin(&self, x: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.sin)(x) }
+    }
+
+    /// Returns the tangent of `x`, in radians.
+    pub fn tan(&self, x: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.tan)(x) }
+    }
+
+    /// Returns `e` raised to the power `x`.
+    pub fn exp(&self, x: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.exp)(x) }
+    }
+
+    /// Returns the natural logarithm of `x`.
+    pub fn ln(&self, x: f32) -> f32 {
+        // Safety: the pointer came from the ROM's soft-float table
+        unsafe { (self.ln)(x) }
+    }
+
+    /// Compares two floats, as [`ROM::fcmp`].
+    ///
+    /// # Returns
+    ///
+    /// The ordering of `a` relative to `b`, or
+    /// [`RomError::UnsupportedRomVersion`] on a B0 (version 1) ROM, which
+    /// lacks this function.
+    pub fn cmp(&self, a: f32, b: f32) -> Result<Ordering, RomError> {
+        match self.cmp {
+            // Safety: the pointer came from the ROM's soft-float table
+            Some(cmp) => Ok(unsafe { cmp(a, b) }.cmp(&0)),
+            None => Err(RomError::UnsupportedRomVersion { version: 1 }),
+        }
+    }
+}
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+// Get a typed function pointer from the soft-float table
+//
+// Safety: table must point to the soft-float table, offset must be within
+// it, and F must be the correct signature for the function at that offset
+unsafe fn sf_table_func<F: Copy>(table: *const *const (), offset: usize) -> F {
+    const { assert!(core::mem::size_of::<F>() == core::mem::size_of::<usize>()) };
+    core::mem::transmute_copy(&*table.add(offset / 4))
+}
+
+#[cfg(feature = "bit-ops")]
+type RomBitFn = unsafe extern "C" fn(u32) -> u32;
+
+#[cfg(feature = "bit-ops")]
+/// The ROM bit manipulation functions.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rp2040_rom::{BitOps, RomFunctionGroup};
+///
+/// let bit_ops = BitOps::lookup().unwrap();
+/// assert_eq!(bit_ops.popcount(0xF0), 4);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct BitOps {
+    popcount: RomBitFn,
+    reverse: RomBitFn,
+    clz: RomBitFn,
+    ctz: RomBitFn,
+}
+
+#[cfg(feature = "bit-ops")]
+impl RomFunctionGroup for BitOps {
+    fn lookup() -> Result<Self, RomError> {
+        // Safety: the types match the datasheet signatures
+        unsafe {
+            Ok(Self {
+                popcount: lookup(rom_codes::POPCOUNT32)?,
+                reverse: lookup(rom_codes::REVERSE32)?,
+                clz: lookup(rom_codes::CLZ32)?,
+                ctz: lookup(rom_codes::CTZ32)?,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "bit-ops")]
+impl BitOps {
+    /// Returns the number of bits set in `x`.
+    pub fn popcount(&self, x: u32) -> u32 {
+        // Safety: the pointer came from the ROM function table
+        unsafe { (self.popcount)(x) }
+    }
+
+    /// Returns `x` with its bits in reverse order.
+    pub fn reverse(&self, x: u32) -> u32 {
+        // Safety: the pointer came from the ROM function table
+        unsafe { (self.reverse)(x) }
+    }
+
+    /// Returns the number of leading zero bits in `x`, or 32 if `x` is 0.
+    pub fn clz(&self, x: u32) -> u32 {
+        // Safety: the pointer came from the ROM function table
+        unsafe { (self.clz)(x) }
+    }
+
+    /// Returns the number of trailing zero bits in `x`, or 32 if `x` is 0.
+    pub fn ctz(&self, x: u32) -> u32 {
+        // Safety: the pointer came from the ROM function table
+        unsafe { (self.ctz)(x) }
+    }
+}
+
+#[cfg(feature = "mem-ops")]
+/// The ROM memory functions.
+///
+/// The ROM has `memset`, `memset4`, `memcpy` and `memcpy44`, but no
+/// `memcmp`, in any RP2040 ROM version, so comparisons should use slice
+/// comparison, such as `a == b` or `a.cmp(b)`.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use rp2040_rom::{MemOps, RomFunctionGroup};
+///
+/// let mem_ops = MemOps::lookup().unwrap();
+/// let mut buf = [0u8; 64];
+/// unsafe {
+///     mem_ops.memset(buf.as_mut_ptr(), 0xFF, buf.len() as u32);
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct MemOps {
+    memset: RomMemsetFn,
+    memset4: RomMemset4Fn,
+    memcpy: RomMemcpyFn,
+    memcpy44: RomMemcpy44Fn,
+}
+
+#[cfg(feature = "mem-ops")]
+impl RomFunctionGroup for MemOps {
+    fn lookup() -> Result<Self, RomError> {
+        // Safety: the types match the datasheet signatures
+        unsafe {
+            Ok(Self {
+                memset: lookup(rom_codes::MEMSET)?,
+                memset4: lookup(rom_codes::MEMSET4)?,
+                memcpy: lookup(rom_codes::MEMCPY)?,
+                memcpy44: lookup(rom_codes::MEMCPY44)?,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "mem-ops")]
+impl MemOps {
+    /// Sets `n` bytes at `ptr` to `c`.
+    ///
+    /// # Returns
+    ///
+    /// `ptr`
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for writes of `n` bytes.
+    pub unsafe fn memset(&self, ptr: *mut u8, c: u8, n: u32) -> *mut u8 {
+        (self.memset)(ptr, c, n)
+    }
+
+    /// Sets `n` bytes at `ptr` to `c`, as [`memset`](Self::memset), but
+    /// faster for word aligned data.
+    ///
+    /// # Returns
+    ///
+    /// `ptr`
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be word aligned and valid for writes of `n` bytes, and `n`
+    /// must be a multiple of 4.
+    pub unsafe fn memset4(&self, ptr: *mut u32, c: u8, n: u32) -> *mut u32 {
+        debug_check!(
+            (ptr as usize).is_multiple_of(4) && n.is_multiple_of(4),
+            "ROM memset4 must be word aligned"
+        );
+        (self.memset4)(ptr, c, n)
+    }
+
+    /// Copies `n` bytes from `src` to `dest`.
+    ///
+    /// # Returns
+    ///
+    /// `dest`
+    ///
+    /// # Safety
+    ///
+    /// `src` must be valid for reads and `dest` valid for writes of `n`
+    /// bytes, and the regions must not overlap.
+    pub unsafe fn memcpy(&self, dest: *mut u8, src: *const u8, n: u32) -> *mut u8 {
+        debug_check_no_overlap(dest as usize, src as usize, n as usize);
+        (self.memcpy)(dest, src, n)
+    }
+
+    /// Copies `n` bytes from `src` to `dest`, as [`memcpy`](Self::memcpy),
+    /// but faster for word aligned data.
+    ///
+    /// # Returns
+    ///
+    /// `dest`
+    ///
+    /// # Safety
+    ///
+    /// As for [`memcpy`](Self::memcpy), and `src` and `dest` must be word
+    /// aligned, and `n` a multiple of 4.
+    pub unsafe fn memcpy44(&self, dest: *mut u32, src: *const u32, n: u32) -> *mut u32 {
+        debug_check!(
+            (dest as usize).is_multiple_of(4)
+                && (src as usize).is_multiple_of(4)
+                && n.is_multiple_of(4),
+            "ROM memcpy44 must be word aligned"
+        );
+        debug_check_no_overlap(dest as usize, src as usize, n as usize);
+        (self.memcpy44)(dest, src, n)
+    }
+
+    /// Copies `count` bytes from `src` to `dst`, as
+    /// [`core::ptr::copy_nonoverlapping`], with the ROM's `memcpy`.
+    ///
+    /// There is no equivalent of [`core::ptr::copy`], as the ROM's `memcpy`
+    /// doesn't handle overlapping regions.
+    ///
+    /// # Safety
+    ///
+    /// As for [`memcpy`](Self::memcpy).
+    pub unsafe fn copy_nonoverlapping(&self, src: *const u8, dst: *mut u8, count: usize) {
+        self.memcpy(dst, src, byte_count(count, 1));
+    }
+
+    /// Copies `count` words from `src` to `dst`, as
+    /// [`core::ptr::copy_nonoverlapping`], with the ROM's `memcpy44`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`memcpy44`](Self::memcpy44), for `count * 4` bytes.
+    pub unsafe fn copy_nonoverlapping_aligned(&self, src: *const u32, dst: *mut u32, count: usize) {
+        self.memcpy44(dst, src, byte_count(count, 4));
+    }
+
+    /// Sets `count` bytes at `dst` to `val`, as [`core::ptr::write_bytes`],
+    /// with the ROM's `memset`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`memset`](Self::memset).
+    pub unsafe fn write_bytes(&self, dst: *mut u8, val: u8, count: usize) {
+        (self.memset)(dst, val, byte_count(count, 1));
+    }
+
+    /// Sets every byte of `count` words at `dst` to `val`, as
+    /// [`core::ptr::write_bytes`], with the ROM's `memset4`.
+    ///
+    /// # Safety
+    ///
+    /// As for [`memset4`](Self::memset4), for `count * 4` bytes.
+    pub unsafe fn write_bytes_aligned(&self, dst: *mut u32, val: u8, count: usize) {
+        self.memset4(dst, val, byte_count(count, 4));
+    }
+}
+
+// Checks the regions the ROM memcpy functions are passed don't overlap, in
+// debug builds, as they copy forwards and would silently corrupt the copy.
+#[cfg(feature = "mem-ops")]
+fn debug_check_no_overlap(dest: usize, src: usize, n: usize) {
+    debug_check!(
+        dest.saturating_add(n) <= src || src.saturating_add(n) <= dest,
+        "ROM memcpy regions must not overlap"
+    );
+}
+
+// Converts a count of `size` byte elements to the byte count the ROM
+// functions take, checking in debug builds that it fits in a u32.  A count
+// which doesn't can't describe a valid region, so in release builds it is
+// clamped to 0, making the call a no-op, rather than saturated or wrapped
+// into a copy or fill over unrelated memory.
+#[cfg(feature = "mem-ops")]
+fn byte_count(count: usize, size: usize) -> u32 {
+    let bytes = count.checked_mul(size).and_then(|n| u32::try_from(n).ok());
+    debug_check!(
+        bytes.is_some(),
+        "MemOps count {} is too large for the ROM functions",
+        count
+    );
+    bytes.unwrap_or(0)
+}
+
+/// [`MemOps`], for code written against the `core::ptr` style method names
+///
+/// Copies are [`copy_nonoverlapping`](MemOps::copy_nonoverlapping) and
+/// [`copy_nonoverlapping_aligned`](MemOps::copy_nonoverlapping_aligned),
+/// taking `(src, dst, count)` as [`core::ptr::copy_nonoverlapping`] does.
+/// There is no `copy` or `copy_aligned`, as the ROM's `memcpy` doesn't
+/// handle the overlapping regions [`core::ptr::copy`] allows.
+#[cfg(feature = "mem-ops")]
+pub type RomMemOps = MemOps;
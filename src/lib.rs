@@ -1,4 +1,5 @@
 #![no_std]
+#![warn(missing_debug_implementations)]
 
 //! This module provides the ability to call Raspberry Pi ROM functions.
 //!
@@ -11,6 +12,31 @@
 //! All functions in this crate are marked as `unsafe` because they involve
 //! direct hardware manipulation and can reset the device.
 //!
+//! # Debug checks
+//!
+//! In debug builds, misuse which would otherwise corrupt flash or memory,
+//! or fault later and obscurely, panics with a description of the problem,
+//! using `defmt::panic!` with the `defmt` feature on bare-metal targets.  In
+//! release builds these checks compile to nothing, so they are no
+//! substitute for meeting the safety requirements.  They check that:
+//!
+//! - ROM function pointers are Thumb addresses
+//! - `reset_usb_boot` and `try_reset_usb_boot_returning` are passed no
+//!   activity pins above GPIO 29 (on the RP2040), and no unknown interface
+//!   disable bits
+//! - `flash_erase_ram`, `flash_program_ram` and the `FlashOps` and
+//!   `FlashAccess` functions are called with interrupts disabled, and the
+//!   first two with sector and page aligned ranges
+//! - `FlashAccess::program` is passed data which isn't in flash, restoring
+//!   XIP before panicking
+//! - The `MemOps` copy functions are passed regions which don't overlap,
+//!   and its word functions word aligned ones
+//!
+//! Flash functions which must be called with XIP already disabled, such as
+//! `flash_range_erase`, aren't checked, as the panic handler would run from
+//! flash.  `FlashOps`, `FlashLayout` and the higher level flash functions
+//! report alignment and bounds errors as `FlashError`s, in every build.
+//!
 //! # Example
 //!
 //! ```rust,no_run
@@ -21,23 +47,381 @@
 //!     ROM::reset_usb_boot(0, 0);
 //! }
 //! ```
+//!
+//! # Features
+//!
+//! The core lookup functions and `reset_usb_boot` are always available.
+//! Larger groups of functions are enabled by features, all off by default:
+//!
+//! | Feature       | Enables                                               |
+//! |---------------|-------------------------------------------------------|
+//! | `flash-ops`   | Flash access functions, `FlashError` and `FlashOps`   |
+//! | `flash-algo`  | Entry points for a probe-rs flash algorithm           |
+//! | `mock`        | `MockFlash`, an in-memory `FlashBackend` for testing  |
+//! | `soft-float`  | Single precision soft-float functions and `FloatOps`  |
+//! | `soft-double` | Double precision soft-float functions                 |
+//! | `bit-ops`     | Bit manipulation functions and `BitOps`               |
+//! | `mem-ops`     | Memory functions and `MemOps`                         |
+//! | `bench`       | `bench_memcpy`, timing the ROM `memcpy`               |
+//! | `cached`      | `CachedRomFn`                                         |
+//! | `pre-init`    | `rom_pre_init`                                        |
+//! | `multicore`   | `launch_core1`, and parking core 1 for flash access   |
+//! | `defmt`       | `defmt::Format` for the error and report types        |
+//! | `custom-base` | `set_rom_base`, for emulators and ROM research        |
+//! | `rp2350`      | Use the RP2350 bootrom instead of the RP2040's        |
+//! | `any-target`  | Allow building for other bare-metal targets           |
+//!
+//! # Running flash operations from RAM
+//!
+//! `ROM::flash_erase_ram` and `ROM::flash_program_ram` perform a complete
+//! flash erase or program sequence from RAM, so can be called from code
+//! running from flash.  They are placed in a `.ram_text` section, which must
+//! be added to your linker script, and copied to RAM before use.  For
+//! example, with `cortex-m-rt`, add to `memory.x`:
+//!
+//! ```text
+//! SECTIONS {
+//!     .ram_text : ALIGN(4) {
+//!         __sram_text = .;
+//!         *(.ram_text .ram_text.*);
+//!         . = ALIGN(4);
+//!         __eram_text = .;
+//!     } > RAM AT > FLASH
+//!     __siram_text = LOADADDR(.ram_text);
+//! } INSERT AFTER .data;
+//! ```
+//!
+//! and copy `__eram_text - __sram_text` bytes from `__siram_text` to
+//! `__sram_text` in a `#[pre_init]` function, or early in `main`.
+//!
+//! Build with at least `opt-level = 1`, so that helpers used while XIP is
+//! disabled are inlined into the RAM resident functions.
+//!
+//! The crate deliberately doesn't offer a way to copy an arbitrary closure
+//! into a RAM buffer and run it there.  A closure's code size isn't known at
+//! compile or run time, Thumb code isn't generally position independent,
+//! and any function it calls would still run from flash, so such a copy
+//! can't be made reliable.  Instead, place your own code in RAM with
+//! `#[link_section = ".data.ram_func"]` and `#[inline(never)]`, as this
+//! crate does, or call through `FlashOps` or `FlashAccess`, whose flash
+//! sequences already run from RAM.
 
 // Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
 //
 // MIT licensed - see https://opensource.org/licenses/MIT
 
+// Every function in this crate calls into the RP2040 ROM at hard-coded
+// addresses, so building for any other bare-metal target is almost certainly
+// a mistake.  This is only a heuristic: stable Rust has no cfg for the
+// Cortex-M0+, so any bare-metal ARM target without atomic compare-and-swap
+// is accepted.  That matches thumbv6m-none-eabi, but also the ARMv4T and
+// ARMv5TE targets, which can't be told apart from it.  Hosted targets are
+// permitted, so the crate can be built and tested on the host.
+#[cfg(all(
+    target_os = "none",
+    not(feature = "rp2350"),
+    not(all(target_arch = "arm", not(target_has_atomic = "ptr"))),
+    not(feature = "any-target"),
+))]
+compile_error!(
+    "rp2040-rom only supports the RP2040 (thumbv6m-none-eabi).  Build for the \
+     host to run tests, using the `mock` feature's `MockFlash` in place of real \
+     flash, or enable the `any-target` feature to override this check."
+);
+
+// With the rp2350 feature, only the RP2350's Arm cores are supported
+#[cfg(all(
+    target_os = "none",
+    feature = "rp2350",
+    not(target_arch = "arm"),
+    not(feature = "any-target"),
+))]
+compile_error!(
+    "rp2040-rom's rp2350 feature only supports the RP2350's Arm cores \
+     (thumbv8m.main-none-eabihf).  Enable the `any-target` feature to override this check."
+);
+
+/// Converts a two character ROM code string, as listed in the datasheet, to
+/// the `(u8, u8)` tuple the lookup functions take, checking at compile time
+/// that it is exactly two ASCII characters.
+///
+/// ```
+/// use rp2040_rom::rom_code;
+///
+/// assert_eq!(rom_code!("RE"), (b'R', b'E'));
+/// ```
+///
+/// Anything else fails to compile:
+///
+/// ```compile_fail
+/// let code = rp2040_rom::rom_code!("RBX");
+/// ```
+///
+/// ```compile_fail
+/// let code = rp2040_rom::rom_code!("é");
+/// ```
+#[macro_export]
+macro_rules! rom_code {
+    ($code:literal) => {{
+        const CODE: (u8, u8) = {
+            let bytes = $code.as_bytes();
+            assert!(bytes.len() == 2, "ROM codes must be exactly two characters");
+            assert!(
+                bytes[0].is_ascii() && bytes[1].is_ascii(),
+                "ROM codes must be ASCII"
+            );
+            (bytes[0], bytes[1])
+        };
+        CODE
+    }};
+}
+
+// Checks a precondition of a wrapper in debug builds, panicking with the
+// message if it doesn't hold.  With the `defmt` feature, on bare-metal
+// targets, where a defmt logger is linked, `defmt::panic!` is used.  In
+// release builds the condition is still type checked, but not evaluated, so
+// the check compiles to nothing.  Used for misuse which would otherwise
+// corrupt flash or memory, or fault later and obscurely; see "Debug checks"
+// in the crate documentation for the list.  Messages must be valid for both
+// `panic!` and `defmt::panic!`.
+macro_rules! debug_check {
+    ($cond:expr, $($arg:tt)+) => {
+        if cfg!(debug_assertions) && !$cond {
+            #[cfg(all(feature = "defmt", target_os = "none"))]
+            defmt::panic!($($arg)+);
+            #[cfg(not(all(feature = "defmt", target_os = "none")))]
+            panic!($($arg)+);
+        }
+    };
+}
+
+#[cfg(feature = "bench")]
+mod bench;
+#[cfg(feature = "cached")]
+mod cached;
+#[cfg(feature = "custom-base")]
+mod custom_base;
+#[cfg(feature = "flash-ops")]
+mod flash;
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+mod float;
+mod fn_types;
+mod group;
+#[cfg(feature = "multicore")]
+mod multicore;
+#[cfg(feature = "pre-init")]
+mod pre_init;
+mod reset;
+pub mod rom_codes;
+pub mod safe;
+#[cfg(not(feature = "rp2350"))]
+mod table;
+mod util;
+
+#[cfg(feature = "bench")]
+pub use bench::{bench_copy_from_slice, bench_memcpy};
+#[cfg(feature = "cached")]
+pub use cached::CachedRomFn;
+#[cfg(feature = "custom-base")]
+pub use custom_base::set_rom_base;
+#[cfg(feature = "mock")]
+pub use flash::MockFlash;
+#[cfg(feature = "flash-ops")]
+pub use flash::{
+    addr_to_flash_offset, flash_offset_to_addr, FlashAddr, FlashBackend, FlashBuffer, FlashError,
+    FlashLayout, FlashSize, PageBuffer, RomFlash, RomFlashBackend, SectorBuffer,
+    FLASH_BLOCK_ERASE_CMD, FLASH_BLOCK_SIZE, FLASH_PAGE_SIZE, FLASH_SECTOR_SIZE, XIP_BASE,
+    XIP_SRAM_BASE,
+};
+#[cfg(feature = "flash-algo")]
+pub use flash::{EraseSector, FlashInit, FlashUninit, ProgramPage};
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub use float::RomMath;
+pub use fn_types::{
+    RomClz32Fn, RomConnectInternalFlashFn, RomCtz32Fn, RomFlashEnterCmdXipFn, RomFlashExitXipFn,
+    RomFlashFlushCacheFn, RomFlashRangeEraseFn, RomFlashRangeProgramFn, RomMemcpy44Fn, RomMemcpyFn,
+    RomMemset4Fn, RomMemsetFn, RomPopcount32Fn, RomRebootFn, RomResetUsbBootFn, RomReverse32Fn,
+    RomTableLookupFn,
+};
+#[cfg(feature = "bit-ops")]
+pub use group::BitOps;
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub use group::FloatOps;
+pub use group::RomFunctionGroup;
+#[cfg(feature = "flash-ops")]
+pub use group::{FlashAccess, FlashOps, FlashRomFns};
+#[cfg(feature = "mem-ops")]
+pub use group::{MemOps, RomMemOps};
+#[cfg(feature = "multicore")]
+pub use multicore::{
+    core1_park_handler, launch_core1, with_core1_parked, PARK_ACK, PARK_RELEASE, PARK_REQUEST,
+};
+#[cfg(feature = "pre-init")]
+pub use pre_init::rom_pre_init;
+pub use reset::{disable_mask, BootloaderReset};
+pub use safe::RomHandle;
+#[cfg(not(feature = "rp2350"))]
+pub use table::RomTableIter;
+pub use util::{Crc32, PlatformError, RomAbi, RomUtil};
+
+/// ROM magic offset, for both the RP2040 and RP2350
+/// From the datasheet:
+///   Magic.  Bytes 0x10 to 0x12 are 'M', 'u', 0x01
+const BOOTROM_MAGIC_OFFSET: u16 = 0x10;
+
+/// The expected ROM magic
+#[cfg(not(feature = "rp2350"))]
+const BOOTROM_MAGIC: [u8; 3] = [b'M', b'u', 0x01];
+#[cfg(feature = "rp2350")]
+const BOOTROM_MAGIC: [u8; 3] = [b'M', b'u', 0x02];
+
+/// ROM version offset, for both the RP2040 and RP2350
+/// From the datasheet:
+///   Version.  Byte 0x13 follows the 3 byte magic 'Mu', 0x01
+const BOOTROM_VERSION_OFFSET: u16 = 0x13;
+
 /// ROM function table offset for the RP2040
 /// From the datasheet:
 ///   Pointer to a public function lookup table (rom_func_table)
-const BOOTROM_FUNC_TABLE_OFFSET: u16 = 0x14;
+///
+/// Like the other `BOOTROM_*_OFFSET` constants, this is the address in the
+/// ROM of a 16-bit pointer.  The offsets, all checked at compile time, are:
+///
+/// | Offset | RP2040                        | RP2350                        |
+/// |--------|-------------------------------|-------------------------------|
+/// | `0x10` | Magic 'M', 'u', 0x01          | Magic 'M', 'u', 0x02          |
+/// | `0x13` | Version byte                  | Version byte                  |
+/// | `0x14` | `BOOTROM_FUNC_TABLE_OFFSET`   | -                             |
+/// | `0x16` | `BOOTROM_DATA_TABLE_OFFSET`   | `BOOTROM_TABLE_LOOKUP_OFFSET` |
+/// | `0x18` | `BOOTROM_TABLE_LOOKUP_OFFSET` | -                             |
+///
+/// See section 2.8.3 (Bootrom Contents) of the RP2040 datasheet, and section
+/// 5.4 (Bootrom APIs) of the RP2350 datasheet.
+#[cfg(not(feature = "rp2350"))]
+pub const BOOTROM_FUNC_TABLE_OFFSET: u16 = 0x14;
+
+/// ROM data table offset for the RP2040
+/// From the datasheet:
+///   Pointer to a public data lookup table (rom_data_table)
+#[cfg(not(feature = "rp2350"))]
+pub const BOOTROM_DATA_TABLE_OFFSET: u16 = 0x16;
 
 /// ROM lookup table offset for the RP2040
 /// From the datasheet:
 ///   Pointer to a helper function (rom_table_lookup())
-const BOOTROM_TABLE_LOOKUP_OFFSET: u16 = 0x18;
+#[cfg(not(feature = "rp2350"))]
+pub const BOOTROM_TABLE_LOOKUP_OFFSET: u16 = 0x18;
+
+/// ROM lookup table offset for the RP2350
+/// From the RP2350 datasheet:
+///   Pointer to a helper function (rom_table_lookup()).  The RP2350 has a
+///   single table, searched using the flags passed to rom_table_lookup(),
+///   rather than separate function and data tables.
+#[cfg(feature = "rp2350")]
+pub const BOOTROM_TABLE_LOOKUP_OFFSET: u16 = 0x16;
+
+/// RP2350 rom_table_lookup() flag to find an Arm secure function
+#[cfg(feature = "rp2350")]
+const RT_FLAG_FUNC_ARM_SEC: u32 = 0x0004;
+
+/// RP2350 rom_table_lookup() flag to find a data entry
+#[cfg(feature = "rp2350")]
+const RT_FLAG_DATA: u32 = 0x0040;
+
+/// Errors returned by ROM functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RomError {
+    /// The function isn't present in this ROM version's function table.
+    /// Contains the two character code of the function.
+    FunctionNotFound { code: (u8, u8) },
+
+    /// The function requires a newer ROM than the one present.  Contains
+    /// the version of the ROM present.
+    UnsupportedRomVersion { version: u8 },
+}
+
+/// Errors returned by [`ROM::try_rom_func_lookup`], distinguishing a ROM
+/// which can't be searched at all from one which lacks the function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum LookupError {
+    /// The ROM magic is invalid, or the function table pointer is null, so
+    /// this isn't the expected chip, or the ROM isn't mapped where expected.
+    TableUnreachable,
+
+    /// The ROM header's pointer to the `rom_table_lookup` helper is null.
+    LookupHelperMissing,
+
+    /// The ROM can be searched, but this ROM version doesn't have the
+    /// function.  Contains the two character code of the function.
+    CodeNotFound(u8, u8),
+}
+
+/// Returned by [`ROM::try_reset_usb_boot_returning`] if the ROM returned
+/// instead of resetting the device
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResetFailed;
+
+/// Which groups of functions the ROM supports, as returned by
+/// [`ROM::available_functions`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RomFunctionReport {
+    /// Flash access functions (erase, program, XIP control)
+    pub flash_ops: bool,
+    /// Bit manipulation functions (popcount, reverse, clz, ctz)
+    pub bit_ops: bool,
+    /// Memory functions (memset, memcpy)
+    pub mem_ops: bool,
+    /// Single precision soft-float table
+    pub soft_float: bool,
+    /// Double precision soft-float table (not present in the B0 ROM)
+    pub soft_double: bool,
+}
+
+/// A validated, non-null pointer to a ROM function, with its signature.
+///
+/// Obtained from [`ROM::lookup_func`], a `RomFunc` proves that the lookup
+/// succeeded, so the function can't accidentally be called through a null
+/// pointer.  `F` is the function pointer type, for example
+/// `unsafe extern "C" fn(u32) -> u32`.
+pub struct RomFunc<F> {
+    ptr: core::ptr::NonNull<core::ffi::c_void>,
+    _marker: core::marker::PhantomData<F>,
+}
+
+impl<F> Clone for RomFunc<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for RomFunc<F> {}
+
+impl<F> core::fmt::Debug for RomFunc<F> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("RomFunc").field(&self.ptr).finish()
+    }
+}
+
+impl<F: Copy> RomFunc<F> {
+    /// Returns the typed function pointer, which can then be called.
+    pub fn get(self) -> F {
+        // Safety: lookup_func checked that F is pointer sized, and its caller
+        // guaranteed that F is the correct signature for this function
+        unsafe { core::mem::transmute_copy(&self.ptr) }
+    }
+
+    /// Returns the raw function pointer.
+    pub fn as_ptr(self) -> *mut core::ffi::c_void {
+        self.ptr.as_ptr()
+    }
+}
 
 /// Object containing exposed ROM functions
 #[allow(clippy::upper_case_acronyms)]
+#[derive(Debug)]
 pub struct ROM {}
 
 /// Public functions
@@ -49,70 +433,733 @@ impl ROM {
     /// * `usb_activity_gpio_pin_mask` - Bitmask of GPIO pins to check for USB activity
     /// * `disable_interface_mask` - Bitmask to disable specific interfaces
     ///
+    /// [`BootloaderReset`] builds these parameters more legibly.
+    ///
     /// # Safety
     ///
     /// This function will reset the device and not return.
     pub unsafe fn reset_usb_boot(usb_activity_gpio_pin_mask: u32, disable_interface_mask: u32) -> ! {
-        // ROM reset_usb_boot function definition
-        type RomResetUsbBootFn =
-            unsafe extern "C" fn(usb_activity_gpio_pin_mask: u32, disable_interface_mask: u32)  -> !;
+        reset::debug_check_reset_masks(usb_activity_gpio_pin_mask, disable_interface_mask);
+
+        #[cfg(not(feature = "rp2350"))]
+        {
+            // ROM reset_usb_boot function definition
+            type RomResetUsbBootFn = unsafe extern "C" fn(
+                usb_activity_gpio_pin_mask: u32,
+                disable_interface_mask: u32,
+            ) -> !;
 
-        // The two character code for the reset_usb_boot function in the
-        // lookup table
-        const ROM_FUNC_RESET_USB_BOOT: (u8, u8) = (b'U', b'B');
+            // Get the function pointer for reset_usb_boot and turn it into a
+            // function we can call
+            let func_ptr = Self::rom_func_lookup(rom_codes::RESET_USB_BOOT);
+            let func: RomResetUsbBootFn = core::mem::transmute(func_ptr);
 
-        // Get the function pointer for reset_usb_boot and turn it into a
-        // function we can call
-        let func_ptr = Self::rom_func_lookup(ROM_FUNC_RESET_USB_BOOT);
-        let func: RomResetUsbBootFn = core::mem::transmute(func_ptr);
+            // Call the function
+            func(usb_activity_gpio_pin_mask, disable_interface_mask);
+        }
 
-        // Call the function
-        func(usb_activity_gpio_pin_mask, disable_interface_mask);
+        // The RP2350 has no reset_usb_boot, so use its generic reboot
+        // function
+        #[cfg(feature = "rp2350")]
+        {
+            let _ =
+                Self::rp2350_reboot_to_bootsel(usb_activity_gpio_pin_mask, disable_interface_mask);
+
+            // The reboot happens after the delay
+            loop {
+                core::hint::spin_loop();
+            }
+        }
     }
-}
 
-// Private functions
-impl ROM {
-    // Get the lookup code for a function, based on the two characters
-    // While the lookup table technically takes a u16, the lookup function
-    // takes a u32, so we'll use a u32 internally.
-    const fn rom_table_code(c1: u8, c2: u8) -> u32 {
+    /// Resets the device and enters USB bootloader mode, returning if the
+    /// ROM fails to do so.
+    ///
+    /// [`ROM::reset_usb_boot`] assumes the ROM never returns.  This variant
+    /// instead declares the ROM function as returning normally, so if
+    /// control does come back (for example due to bad arguments, or not
+    /// running on an RP2040) the caller can take recovery action, such as a
+    /// watchdog reset.
+    ///
+    /// # Parameters
+    ///
+    /// As for [`ROM::reset_usb_boot`].
+    ///
+    /// # Returns
+    ///
+    /// Only returns if the reset failed.
+    ///
+    /// # Safety
+    ///
+    /// This function will usually reset the device and not return.
+    pub unsafe fn try_reset_usb_boot_returning(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+    ) -> ResetFailed {
+        reset::debug_check_reset_masks(usb_activity_gpio_pin_mask, disable_interface_mask);
+
+        #[cfg(not(feature = "rp2350"))]
+        {
+            type RomResetUsbBootReturningFn =
+                unsafe extern "C" fn(usb_activity_gpio_pin_mask: u32, disable_interface_mask: u32);
+
+            let func_ptr = Self::rom_func_lookup(rom_codes::RESET_USB_BOOT);
+            if !func_ptr.is_null() {
+                let func: RomResetUsbBootReturningFn = core::mem::transmute(func_ptr);
+                func(usb_activity_gpio_pin_mask, disable_interface_mask);
+            }
+        }
+
+        // On the RP2350, reboot returns an error code on failure
+        #[cfg(feature = "rp2350")]
+        {
+            let _ =
+                Self::rp2350_reboot_to_bootsel(usb_activity_gpio_pin_mask, disable_interface_mask);
+        }
+
+        ResetFailed
+    }
+
+    /// Resets the device and enters USB bootloader mode, as
+    /// [`ROM::try_reset_usb_boot_returning`], calling `on_return` if the
+    /// ROM returns, rather than spinning.
+    ///
+    /// For a defined recovery, such as a watchdog reset, without needing a
+    /// watchdog dependency here.  `on_return` running at all is a serious
+    /// anomaly - the wrong chip, or a corrupt ROM.  It is a function pointer,
+    /// rather than a closure, as a `FnOnce() -> !` bound needs the unstable
+    /// never type.
+    ///
+    /// # Parameters
+    ///
+    /// * `usb_activity_gpio_pin_mask`, `disable_interface_mask` - As for
+    ///   [`ROM::reset_usb_boot`]
+    /// * `on_return` - Called if the reset fails
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::try_reset_usb_boot_returning`].
+    pub unsafe fn reset_usb_boot_or(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+        on_return: fn() -> !,
+    ) -> ! {
+        let ResetFailed =
+            Self::try_reset_usb_boot_returning(usb_activity_gpio_pin_mask, disable_interface_mask);
+        on_return()
+    }
+
+    /// Gets the lookup code for a ROM function or data table entry, based on
+    /// its two character identifier, as used by the ROM's
+    /// `rom_table_lookup()`.
+    ///
+    /// While the lookup table technically stores a u16, the lookup function
+    /// takes a u32, so a u32 is returned.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rp2040_rom::ROM;
+    ///
+    /// // The code for reset_usb_boot
+    /// const CODE: u32 = ROM::rom_table_code(b'U', b'B');
+    /// assert_eq!(CODE, 0x4255);
+    /// ```
+    pub const fn rom_table_code(c1: u8, c2: u8) -> u32 {
         (c1 as u32) | ((c2 as u32) << 8)
     }
 
-    // Convert a u16 provided by the ROM lookup table to a pointer
-    unsafe fn rom_hword_as_ptr(rom_address: u16) -> *mut core::ffi::c_void {
-        // Convert to usize first
-        let addr_val = rom_address as usize;
+    /// Looks up a function in the ROM function table, based on the two
+    /// characters used to index it.
+    ///
+    /// This can be used to call ROM functions not yet wrapped by this crate.
+    /// The returned pointer must be transmuted to the correct
+    /// `unsafe extern "C" fn` type before calling it.
+    ///
+    /// # Parameters
+    ///
+    /// * `code` - The two character code for the function, e.g.
+    ///   `(b'U', b'B')` for `reset_usb_boot`
+    ///
+    /// # Returns
+    ///
+    /// A pointer to the function, or null if the code isn't present in the
+    /// function table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.  `code` must be a valid two character
+    /// ROM function table key, as listed in the RP2040 datasheet, and the
+    /// returned pointer must only be called using the signature documented
+    /// for that function.
+    pub unsafe fn rom_func_lookup(code: (u8, u8)) -> *mut core::ffi::c_void {
+        // The cache holds the functions of the ROM at address 0
+        #[cfg(feature = "pre-init")]
+        if Self::rom_base() == 0 {
+            if let Some(ptr) = pre_init::cached_func(code) {
+                return ptr;
+            }
+        }
+
+        #[cfg(not(feature = "rp2350"))]
+        let table = BOOTROM_FUNC_TABLE_OFFSET;
+        #[cfg(feature = "rp2350")]
+        let table = RT_FLAG_FUNC_ARM_SEC;
+
+        let ptr = Self::rom_table_lookup(table, code);
+
+        // Cortex-M only executes Thumb code, so a function pointer without
+        // its LSB set means something is badly wrong - catch it here rather
+        // than as a fault when it's called
+        debug_check!(
+            ptr.is_null() || ptr as usize & 1 == 1,
+            "ROM function pointer {:#x} is not a Thumb address",
+            ptr as usize
+        );
+
+        ptr
+    }
+
+    /// Looks up a function in the ROM function table, as
+    /// [`ROM::rom_func_lookup`], after checking the ROM header can be used,
+    /// so a failure says why the function couldn't be found.
+    ///
+    /// [`LookupError::TableUnreachable`] and
+    /// [`LookupError::LookupHelperMissing`] mean the board or ROM mapping is
+    /// wrong, so no lookup will succeed.  [`LookupError::CodeNotFound`]
+    /// means this ROM version lacks the function.
+    ///
+    /// # Parameters
+    ///
+    /// * `code` - The two character code for the function
+    ///
+    /// # Returns
+    ///
+    /// A pointer to the function, or the reason it wasn't found.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::rom_func_lookup`], except that calling this on a chip
+    /// other than an RP2040 returns an error rather than calling into an
+    /// unknown ROM, as long as the ROM region is readable.
+    pub unsafe fn try_rom_func_lookup(
+        code: (u8, u8),
+    ) -> Result<*mut core::ffi::c_void, LookupError> {
+        Self::rom_lookup_check()?;
 
-        // Create pointer AND dereference it
-        let value = unsafe { *(addr_val as *const u16) };
+        let ptr = Self::rom_func_lookup(code);
+        if ptr.is_null() {
+            return Err(LookupError::CodeNotFound(code.0, code.1));
+        }
 
-        // Convert value to pointer size then to void pointer
-        value as usize as *mut core::ffi::c_void
+        Ok(ptr)
     }
 
-    // Get the pointer for a function, based on the two characters used to
-    // index it
-    unsafe fn rom_func_lookup(code: (u8, u8)) -> *mut core::ffi::c_void {
-        // The ROM reset_usb_boot function definition
+    /// Looks up a function in the ROM function table, returning a typed
+    /// [`RomFunc`] which can be called without further transmuting.
+    ///
+    /// # Parameters
+    ///
+    /// * `code` - The two character code for the function
+    ///
+    /// # Returns
+    ///
+    /// The function, or `None` if the code isn't present in the function
+    /// table.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::rom_func_lookup`].  In addition, `F` must be the
+    /// `unsafe extern "C" fn` type matching the signature documented for this
+    /// function.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rp2040_rom::ROM;
+    ///
+    /// type Popcount32Fn = unsafe extern "C" fn(u32) -> u32;
+    ///
+    /// unsafe {
+    ///     if let Some(popcount32) = ROM::lookup_func::<Popcount32Fn>((b'P', b'3')) {
+    ///         assert_eq!(popcount32.get()(0xF0), 4);
+    ///     }
+    /// }
+    /// ```
+    pub unsafe fn lookup_func<F: Copy>(code: (u8, u8)) -> Option<RomFunc<F>> {
+        // F must be a (thin) function pointer for the transmute in get() to
+        // be valid
+        const { assert!(core::mem::size_of::<F>() == core::mem::size_of::<usize>()) };
+
+        core::ptr::NonNull::new(Self::rom_func_lookup(code)).map(|ptr| RomFunc {
+            ptr,
+            _marker: core::marker::PhantomData,
+        })
+    }
+
+    /// Checks whether a function is present in the ROM function table.
+    ///
+    /// Some ROM functions are only present in certain ROM versions.  This
+    /// allows code to gracefully degrade, rather than calling through a null
+    /// pointer.  Functions in this crate which can't find their ROM function
+    /// return [`RomError::FunctionNotFound`].
+    ///
+    /// # Parameters
+    ///
+    /// * `code` - The two character code for the function
+    pub fn func_exists(code: (u8, u8)) -> bool {
+        // Safety: looking up a code, even an invalid one, just walks the ROM
+        // function table and has no side effects
+        unsafe { !Self::rom_func_lookup(code).is_null() }
+    }
+
+    /// Probes the ROM for representative functions from each group, to
+    /// report what the ROM supports.
+    ///
+    /// Intended for startup code which needs to choose between a ROM and
+    /// software implementation.
+    pub fn available_functions() -> RomFunctionReport {
+        // Functions and data entries probed for each group
+        const FLASH_OPS: [(u8, u8); 6] = [
+            rom_codes::CONNECT_INTERNAL_FLASH,
+            rom_codes::FLASH_EXIT_XIP,
+            rom_codes::FLASH_RANGE_ERASE,
+            rom_codes::FLASH_RANGE_PROGRAM,
+            rom_codes::FLASH_FLUSH_CACHE,
+            rom_codes::FLASH_ENTER_CMD_XIP,
+        ];
+        const BIT_OPS: [(u8, u8); 4] = [
+            rom_codes::POPCOUNT32,
+            rom_codes::REVERSE32,
+            rom_codes::CLZ32,
+            rom_codes::CTZ32,
+        ];
+        const MEM_OPS: [(u8, u8); 4] = [
+            rom_codes::MEMSET,
+            rom_codes::MEMSET4,
+            rom_codes::MEMCPY,
+            rom_codes::MEMCPY44,
+        ];
+
+        // Safety: looking up a code has no side effects
+        let data_exists = |code| unsafe { !Self::rom_data_lookup(code).is_null() };
+
+        RomFunctionReport {
+            flash_ops: FLASH_OPS.iter().all(|&code| Self::func_exists(code)),
+            bit_ops: BIT_OPS.iter().all(|&code| Self::func_exists(code)),
+            mem_ops: MEM_OPS.iter().all(|&code| Self::func_exists(code)),
+            soft_float: data_exists(rom_codes::SOFT_FLOAT_TABLE),
+            soft_double: data_exists(rom_codes::SOFT_DOUBLE_TABLE),
+        }
+    }
+
+    /// Looks up several functions in the ROM function table in a single
+    /// pass.
+    ///
+    /// # Parameters
+    ///
+    /// * `codes` - The two character codes of the functions to look up
+    /// * `ptrs` - Receives the function pointers, in the same order as
+    ///   `codes`.  Must be at least as long as `codes`.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` if every function was found, or [`RomError::FunctionNotFound`]
+    /// for the first code which wasn't.  `ptrs` entries before the failing
+    /// code are filled in.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::rom_func_lookup`].
+    ///
+    /// # Panics
+    ///
+    /// If `ptrs` is shorter than `codes`.
+    pub unsafe fn lookup_many(
+        codes: &[(u8, u8)],
+        ptrs: &mut [*mut core::ffi::c_void],
+    ) -> Result<(), RomError> {
+        assert!(
+            ptrs.len() >= codes.len(),
+            "lookup_many: ptrs shorter than codes"
+        );
+
+        for (&code, ptr) in codes.iter().zip(ptrs.iter_mut()) {
+            *ptr = Self::rom_func_lookup(code);
+            if ptr.is_null() {
+                return Err(RomError::FunctionNotFound { code });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Looks up an entry in the ROM data table, based on the two characters
+    /// used to index it.
+    ///
+    /// # Parameters
+    ///
+    /// * `code` - The two character code for the data entry, e.g.
+    ///   `(b'C', b'R')` for the copyright string
+    ///
+    /// # Returns
+    ///
+    /// A pointer to the data, or null if the code isn't present in the data
+    /// table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.  `code` must be a valid two character
+    /// ROM data table key, as listed in the RP2040 datasheet, and the returned
+    /// pointer must only be dereferenced as the type documented for that
+    /// entry.
+    pub unsafe fn rom_data_lookup(code: (u8, u8)) -> *const core::ffi::c_void {
+        #[cfg(not(feature = "rp2350"))]
+        let table = BOOTROM_DATA_TABLE_OFFSET;
+        #[cfg(feature = "rp2350")]
+        let table = RT_FLAG_DATA;
+
+        Self::rom_table_lookup(table, code)
+    }
+
+    /// Looks up an entry in the ROM data table, as [`ROM::rom_data_lookup`],
+    /// returning it as a pointer to an array of words.
+    ///
+    /// The word array entries in the RP2040 ROM, and their known lengths,
+    /// are:
+    ///
+    /// | Code | Contents                    | Words                          |
+    /// |------|-----------------------------|--------------------------------|
+    /// | `GR` | Git revision                | 1                              |
+    /// | `SF` | Soft-float function table   | 21 in V1, at least 23 from V2  |
+    /// | `SD` | Soft-double function table  | V2 and later, at least 28      |
+    ///
+    /// # Returns
+    ///
+    /// A pointer to the first word, or null if the code isn't present in the
+    /// data table.
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::rom_data_lookup`].
+    pub unsafe fn rom_data_ptr(code: (u8, u8)) -> *const u32 {
+        Self::rom_data_lookup(code) as *const u32
+    }
+
+    /// Returns word `index` of the ROM data table entry `code`, as found by
+    /// [`ROM::rom_data_ptr`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::rom_data_lookup`].  In addition, `code` must be present
+    /// in the data table, and `index` must be within its array - see
+    /// [`ROM::rom_data_ptr`] for the known lengths.
+    pub unsafe fn rom_data_word(code: (u8, u8), index: usize) -> u32 {
+        *Self::rom_data_ptr(code).add(index)
+    }
+
+    /// Looks up an entry in a caller supplied table, using the ROM's
+    /// `rom_table_lookup` helper, as the crate does for the ROM's own tables.
+    ///
+    /// The table must be in the format the ROM expects: an array of `u16`
+    /// pairs, each a code packed as by [`ROM::rom_table_code`] followed by a
+    /// 16-bit pointer, terminated by a code of zero.  As the pointers are 16
+    /// bits, entries can only point within the first 64KB of the address
+    /// space.
+    ///
+    /// # Parameters
+    ///
+    /// * `table` - The table to search
+    /// * `code` - The two character code for the entry
+    ///
+    /// # Returns
+    ///
+    /// The entry's pointer, or null if the code isn't in the table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040, and `table` must point to a valid,
+    /// terminated table.
+    #[cfg(not(feature = "rp2350"))]
+    pub unsafe fn table_lookup(table: *const u16, code: (u8, u8)) -> *mut core::ffi::c_void {
+        // The ROM rom_table_lookup function definition
         type RomTableLookupFn =
             unsafe extern "C" fn(table: *const u16, code: u32) -> *mut core::ffi::c_void;
 
+        // Reinterprets the lookup function's address as a function pointer.
+        // A union is used rather than transmuting the data pointer, as Rust
+        // doesn't guarantee data and function pointers share a
+        // representation, so the reinterpretation is kept explicit and local.
+        // Both are 32-bit addresses on Cortex-M.  See "Function pointers" in
+        // the Unsafe Code Guidelines' layout chapter.
+        union LookupFnPtr {
+            data: *mut core::ffi::c_void,
+            func: RomTableLookupFn,
+        }
+
         // Get the 32-bit code for the two characters that we need to pass
         // into the lookup function
         let (c1, c2) = code;
         let code = Self::rom_table_code(c1, c2);
 
-        // Get the function table address
-        let func_table_addr = Self::rom_hword_as_ptr(BOOTROM_FUNC_TABLE_OFFSET);
-        let func_table = func_table_addr as *const u16;
+        // Get the lookup function address
+        let lookup_addr = Self::rom_hword_as_ptr(BOOTROM_TABLE_LOOKUP_OFFSET);
+        let rom_table_lookup = LookupFnPtr { data: lookup_addr }.func;
+
+        // Use the lookup function to lookup this code
+        rom_table_lookup(table, code)
+    }
+
+    /// Reads the 16-bit pointer at `rom_address` in the ROM, and returns it
+    /// as a full pointer.
+    ///
+    /// The ROM header and tables store pointers as 16-bit ROM addresses -
+    /// for example, the lookup helper pointer at
+    /// [`BOOTROM_TABLE_LOOKUP_OFFSET`].  For custom ROM tooling which needs
+    /// entries this crate doesn't wrap.  Both `rom_address` and the value
+    /// read are offset by the ROM base, which is 0 unless changed with the
+    /// `custom-base` feature.
+    ///
+    /// # Safety
+    ///
+    /// `rom_address` must be 2 byte aligned and within the ROM.  Any
+    /// halfword in the ROM can be read, but only one which holds a pointer,
+    /// such as those at the `BOOTROM_*_OFFSET` addresses, gives a meaningful
+    /// result.  The returned pointer must only be dereferenced as the type
+    /// of the entry it points to.
+    pub unsafe fn rom_hword_as_ptr(rom_address: u16) -> *mut core::ffi::c_void {
+        // Read the 16-bit pointer
+        let value = Self::rom_hword(rom_address);
+
+        // Convert value to pointer size, offsetting by the ROM base, then to
+        // void pointer
+        (Self::rom_base() + value as usize) as *mut core::ffi::c_void
+    }
+
+    /// Returns the ROM's copyright string.
+    ///
+    /// The string is found via the data table entry `CR`.  Useful for
+    /// diagnostic output, and as a sanity check that the expected ROM is
+    /// present.
+    ///
+    /// The returned reference is valid for the lifetime of the program, as
+    /// it points into the ROM, which is always mapped.
+    ///
+    /// # Returns
+    ///
+    /// The copyright string, or an empty string if the ROM has no copyright
+    /// string entry.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn copyright_string() -> &'static core::ffi::CStr {
+        let ptr = Self::rom_data_lookup(rom_codes::COPYRIGHT) as *const core::ffi::c_char;
+        if ptr.is_null() {
+            return c"";
+        }
+
+        core::ffi::CStr::from_ptr(ptr)
+    }
+}
+
+// Private functions
+impl ROM {
+    // Reboot the RP2350 into BOOTSEL mode using its generic reboot function.
+    // This takes a single GPIO pin number, rather than a mask, so the lowest
+    // pin in the mask is used.  Only returns on failure, with the ROM's
+    // error code.
+    #[cfg(feature = "rp2350")]
+    unsafe fn rp2350_reboot_to_bootsel(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+    ) -> i32 {
+        type RomRebootFn = unsafe extern "C" fn(flags: u32, delay_ms: u32, p0: u32, p1: u32) -> i32;
+        const REBOOT2_FLAG_REBOOT_TYPE_BOOTSEL: u32 = 0x2;
+        const REBOOT2_FLAG_NO_RETURN_ON_SUCCESS: u32 = 0x100;
+        const BOOTSEL_FLAG_GPIO_PIN_SPECIFIED: u32 = 0x20;
+        const REBOOT_DELAY_MS: u32 = 10;
+
+        let mut bootsel_flags = disable_interface_mask;
+        let mut gpio_pin = 0;
+        if usb_activity_gpio_pin_mask != 0 {
+            bootsel_flags |= BOOTSEL_FLAG_GPIO_PIN_SPECIFIED;
+            gpio_pin = usb_activity_gpio_pin_mask.trailing_zeros();
+        }
+
+        let func_ptr = Self::rom_func_lookup(rom_codes::REBOOT);
+        if func_ptr.is_null() {
+            return -1;
+        }
+        let func: RomRebootFn = core::mem::transmute(func_ptr);
+        func(
+            REBOOT2_FLAG_REBOOT_TYPE_BOOTSEL | REBOOT2_FLAG_NO_RETURN_ON_SUCCESS,
+            REBOOT_DELAY_MS,
+            bootsel_flags,
+            gpio_pin,
+        )
+    }
+
+    // Get the ROM magic bytes
+    fn rom_magic() -> [u8; 3] {
+        let magic = (Self::rom_base() + BOOTROM_MAGIC_OFFSET as usize) as *const [u8; 3];
+
+        // Safety: the magic is always mapped
+        unsafe { core::ptr::read_volatile(magic) }
+    }
+
+    // Check the ROM magic is as expected
+    fn rom_magic_valid() -> bool {
+        Self::rom_magic() == BOOTROM_MAGIC
+    }
+
+    // Get the ROM version byte: on the RP2040, 1 for the B0 chip revision, 2
+    // for B1 and 3 for B2
+    fn rom_version() -> u8 {
+        let version = (Self::rom_base() + BOOTROM_VERSION_OFFSET as usize) as *const u8;
+
+        // Safety: the version byte is always mapped
+        unsafe { core::ptr::read_volatile(version) }
+    }
+
+    // Get the address the ROM is mapped at - always 0 on real silicon
+    #[inline(always)]
+    fn rom_base() -> usize {
+        #[cfg(feature = "custom-base")]
+        {
+            custom_base::rom_base()
+        }
+        #[cfg(not(feature = "custom-base"))]
+        {
+            0
+        }
+    }
+
+    // Check the ROM header is usable for lookups: the magic is valid, and
+    // the table and lookup helper pointers aren't null
+    fn rom_lookup_check() -> Result<(), LookupError> {
+        if !Self::rom_magic_valid() {
+            return Err(LookupError::TableUnreachable);
+        }
+
+        // Safety: the magic is valid, so the header is mapped
+        #[cfg(not(feature = "rp2350"))]
+        if unsafe { Self::rom_hword(BOOTROM_FUNC_TABLE_OFFSET) } == 0 {
+            return Err(LookupError::TableUnreachable);
+        }
+        if unsafe { Self::rom_hword(BOOTROM_TABLE_LOOKUP_OFFSET) } == 0 {
+            return Err(LookupError::LookupHelperMissing);
+        }
+
+        Ok(())
+    }
+
+    // Read a u16 from the ROM, offsetting by the ROM base
+    unsafe fn rom_hword(rom_address: u16) -> u16 {
+        let addr_val = Self::rom_base() + rom_address as usize;
+        unsafe { *(addr_val as *const u16) }
+    }
+
+    // Get the pointer for a function or data entry, based on the offset of
+    // the pointer to the table to search, and the two characters used to
+    // index the entry
+    #[cfg(not(feature = "rp2350"))]
+    unsafe fn rom_table_lookup(table_offset: u16, code: (u8, u8)) -> *mut core::ffi::c_void {
+        // Get the table address
+        let table_addr = Self::rom_hword_as_ptr(table_offset);
+        let table = table_addr as *const u16;
+
+        Self::table_lookup(table, code)
+    }
+
+    // Get the pointer for a function or data entry on the RP2350, based on
+    // the flags selecting the type of entry, and the two characters used to
+    // index the entry
+    #[cfg(feature = "rp2350")]
+    unsafe fn rom_table_lookup(flags: u32, code: (u8, u8)) -> *mut core::ffi::c_void {
+        // The RP2350 ROM rom_table_lookup function definition
+        type RomTableLookupFn =
+            unsafe extern "C" fn(code: u32, flags: u32) -> *mut core::ffi::c_void;
+
+        // Reinterprets the lookup function's address, as in the RP2040
+        // table_lookup()
+        union LookupFnPtr {
+            data: *mut core::ffi::c_void,
+            func: RomTableLookupFn,
+        }
+
+        let (c1, c2) = code;
+        let code = Self::rom_table_code(c1, c2);
 
         // Get the lookup function address
         let lookup_addr = Self::rom_hword_as_ptr(BOOTROM_TABLE_LOOKUP_OFFSET);
-        let rom_table_lookup: RomTableLookupFn = core::mem::transmute(lookup_addr);
+        let rom_table_lookup = LookupFnPtr { data: lookup_addr }.func;
 
         // Use the lookup function to lookup this code
-        rom_table_lookup(func_table, code)
+        rom_table_lookup(code, flags)
+    }
+}
+
+// Check the lookup code packing at compile time
+const _: () = assert!(ROM::rom_table_code(b'P', b'3') == 0x3350);
+
+// Check the ROM header offsets against the datasheets at compile time, as a
+// typo here would silently break every lookup
+const _: () = {
+    assert!(BOOTROM_MAGIC_OFFSET == 0x10);
+    assert!(BOOTROM_VERSION_OFFSET == 0x13);
+};
+#[cfg(not(feature = "rp2350"))]
+const _: () = {
+    assert!(BOOTROM_FUNC_TABLE_OFFSET == 0x14);
+    assert!(BOOTROM_DATA_TABLE_OFFSET == 0x16);
+    assert!(BOOTROM_TABLE_LOOKUP_OFFSET == 0x18);
+};
+#[cfg(feature = "rp2350")]
+const _: () = {
+    assert!(BOOTROM_TABLE_LOOKUP_OFFSET == 0x16);
+    assert!(RT_FLAG_FUNC_ARM_SEC == 0x0004);
+    assert!(RT_FLAG_DATA == 0x0040);
+};
+
+// Function pointers are reinterpreted from the ROM's data pointers
+// throughout.  transmute checks the size of each wrapper's conversion at
+// compile time, but the lookup function unions and RomFunc's transmute_copy
+// don't, so check the representation they rely on here
+const _: () = {
+    use core::ffi::c_void;
+    use core::mem::size_of;
+
+    assert!(size_of::<fn()>() == size_of::<*const ()>());
+    assert!(size_of::<unsafe extern "C" fn()>() == size_of::<*const ()>());
+    #[cfg(not(feature = "rp2350"))]
+    assert!(
+        size_of::<unsafe extern "C" fn(*const u16, u32) -> *mut c_void>()
+            == size_of::<*mut c_void>()
+    );
+    #[cfg(feature = "rp2350")]
+    assert!(size_of::<unsafe extern "C" fn(u32, u32) -> *mut c_void>() == size_of::<*mut c_void>());
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rom_table_code_packing() {
+        assert_eq!(ROM::rom_table_code(b'U', b'B'), 0x4255);
+        assert_eq!(ROM::rom_table_code(b'M', b'C'), 0x434D);
+    }
+
+    #[test]
+    fn rom_code_macro() {
+        assert_eq!(rom_code!("UB"), (b'U', b'B'));
+        assert_eq!(rom_code!("P3"), (b'P', b'3'));
+    }
+
+    #[test]
+    fn rom_table_code_high_bits_zero() {
+        assert_eq!(ROM::rom_table_code(0xFF, 0xFF), 0xFFFF);
+        assert_eq!(ROM::rom_table_code(b'U', b'B') & 0xFFFF_0000, 0);
     }
 }
@@ -0,0 +1,191 @@
+//! Launching core 1, and parking it while core 0 calls ROM functions which
+//! need the other core idle, such as the flash functions.
+//!
+//! [`launch_core1`] starts core 1 from the ROM's `wait_for_vector` loop,
+//! which it runs after reset.  The park handshake then uses the SIO
+//! inter-core FIFOs:
+//!
+//! 1. Core 0 pushes [`PARK_REQUEST`] to core 1's FIFO.
+//! 2. Core 1, in [`core1_park_handler`], pops the request, pushes
+//!    [`PARK_ACK`] back, then spins in RAM.
+//! 3. Core 0 pops the acknowledgement, runs the closure, then pushes
+//!    [`PARK_RELEASE`].
+//! 4. Core 1 pops the release, and returns from [`core1_park_handler`].
+//!
+//! Core 1 must therefore be launched with code which calls
+//! [`core1_park_handler`] whenever its FIFO has data - typically from its
+//! `SIO_IRQ_PROC1` interrupt handler, with that interrupt enabled at the
+//! highest priority.  Any code which uses the FIFOs for other purposes must
+//! not send these words.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use core::ptr::{read_volatile, write_volatile};
+
+/// SIO registers
+const SIO_BASE: usize = 0xd000_0000;
+const SIO_FIFO_ST: *const u32 = (SIO_BASE + 0x50) as *const u32;
+const SIO_FIFO_WR: *mut u32 = (SIO_BASE + 0x54) as *mut u32;
+const SIO_FIFO_RD: *const u32 = (SIO_BASE + 0x58) as *const u32;
+
+/// SIO FIFO_ST bits: the read FIFO has data, and the write FIFO has space
+const SIO_FIFO_ST_VLD: u32 = 1 << 0;
+const SIO_FIFO_ST_RDY: u32 = 1 << 1;
+
+/// Cortex-M vector table offset register
+const VTOR: *const u32 = 0xe000_ed08 as *const u32;
+
+/// Sent by core 0 to request core 1 parks - "PARK"
+pub const PARK_REQUEST: u32 = 0x5041_524B;
+
+/// Sent by core 1 once parked - "PKAK"
+pub const PARK_ACK: u32 = 0x504B_414B;
+
+/// Sent by core 0 to release core 1 - "RLSE"
+pub const PARK_RELEASE: u32 = 0x524C_5345;
+
+/// Launches core 1, which must be in the ROM's `wait_for_vector` loop, as it
+/// is after reset, running `entry` with the stack pointer set to
+/// `stack_top`.
+///
+/// Core 0 sends core 1 this sequence of words over the SIO FIFO:
+///
+/// 1. `0`, `0` and `1`, to resynchronise
+/// 2. The vector table address, the current `VTOR` of core 0
+/// 3. `stack_top`
+/// 4. `entry`
+///
+/// After each word it waits for core 1 to echo it back.  If the echo
+/// doesn't match, it starts the sequence again.  Before each `0`, it drains
+/// its own FIFO, discarding anything left over, and sends an event, in case
+/// core 1 is waiting for one.
+///
+/// Core 1 needs its FIFO empty and the sequence in order.  If core 1 isn't
+/// in `wait_for_vector`, for example because it has already been launched
+/// and not reset, nothing echoes the words back, and this never returns.
+///
+/// # Safety
+///
+/// Must be called from core 0, with core 1 in `wait_for_vector`.
+/// `stack_top` must be the top of a region of RAM, 8 byte aligned, used by
+/// nothing else, and large enough for `entry`.  Nothing else may use the
+/// inter-core FIFOs during this call.
+pub unsafe fn launch_core1(stack_top: *mut u32, entry: extern "C" fn() -> !) {
+    let sequence = [
+        0,
+        0,
+        1,
+        read_volatile(VTOR),
+        stack_top as u32,
+        entry as usize as u32,
+    ];
+
+    let mut index = 0;
+    while index < sequence.len() {
+        let word = sequence[index];
+        if word == 0 {
+            fifo_drain();
+            #[cfg(target_arch = "arm")]
+            core::arch::asm!("sev", options(nostack, preserves_flags));
+        }
+
+        fifo_push(word);
+        index = if fifo_pop() == word { index + 1 } else { 0 };
+    }
+}
+
+/// Runs `f` on core 0 with core 1 parked in RAM, so `f` can safely call ROM
+/// functions which require the other core not to be executing from flash,
+/// such as the flash functions.
+///
+/// Blocks until core 1 acknowledges the park request, and releases it once
+/// `f` returns.  See the module documentation for the handshake.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// let result = unsafe {
+///     rp2040_rom::with_core1_parked(|| {
+///         cortex_m::interrupt::free(|_| ROM::flash_erase_ram(0x10_0000, 4096))
+///     })
+/// };
+/// ```
+///
+/// # Safety
+///
+/// Must be called from core 0, and core 1 must be running code which calls
+/// [`core1_park_handler`] when its FIFO has data.  Otherwise this never
+/// returns.  Nothing else may use the inter-core FIFOs during this call.
+pub unsafe fn with_core1_parked<R>(f: impl FnOnce() -> R) -> R {
+    fifo_push(PARK_REQUEST);
+    while fifo_pop() != PARK_ACK {}
+
+    let result = f();
+
+    fifo_push(PARK_RELEASE);
+    result
+}
+
+/// Handles a park request from [`with_core1_parked`], on core 1.
+///
+/// Pops a word from core 1's FIFO, and if it is [`PARK_REQUEST`],
+/// acknowledges it and spins, from RAM, until core 0 sends
+/// [`PARK_RELEASE`].  Other words are ignored.  Call this from core 1's
+/// `SIO_IRQ_PROC1` interrupt handler:
+///
+/// ```rust,ignore
+/// #[interrupt]
+/// fn SIO_IRQ_PROC1() {
+///     unsafe { rp2040_rom::core1_park_handler() };
+/// }
+/// ```
+///
+/// Interrupts at a higher priority than the caller can still run while
+/// parked, so must not execute from flash.
+///
+/// # Safety
+///
+/// Must only be called on core 1, when its FIFO has data.  This function is
+/// placed in `.data`, so is copied to RAM at startup, and must not be called
+/// from a `#[pre_init]` function.
+#[inline(never)]
+#[cfg_attr(
+    all(target_arch = "arm", target_os = "none"),
+    link_section = ".data.ram_func"
+)]
+pub unsafe fn core1_park_handler() {
+    if fifo_pop() != PARK_REQUEST {
+        return;
+    }
+
+    fifo_push(PARK_ACK);
+    while fifo_pop() != PARK_RELEASE {}
+}
+
+// Pushes a word to the other core's FIFO, waiting for space
+#[inline(always)]
+unsafe fn fifo_push(value: u32) {
+    while read_volatile(SIO_FIFO_ST) & SIO_FIFO_ST_RDY == 0 {}
+    write_volatile(SIO_FIFO_WR, value);
+
+    // Wake the other core, in case it is waiting for an event
+    #[cfg(target_arch = "arm")]
+    core::arch::asm!("sev", options(nostack, preserves_flags));
+}
+
+// Discards everything in this core's FIFO
+#[inline(always)]
+unsafe fn fifo_drain() {
+    while read_volatile(SIO_FIFO_ST) & SIO_FIFO_ST_VLD != 0 {
+        let _ = read_volatile(SIO_FIFO_RD);
+    }
+}
+
+// Pops a word from this core's FIFO, waiting for data
+#[inline(always)]
+unsafe fn fifo_pop() -> u32 {
+    while read_volatile(SIO_FIFO_ST) & SIO_FIFO_ST_VLD == 0 {}
+    read_volatile(SIO_FIFO_RD)
+}
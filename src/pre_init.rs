@@ -0,0 +1,124 @@
+//! Pre-init hook, resolving common ROM function pointers before `main`.
+//!
+//! `cortex-m-rt`'s `#[pre_init]` function runs before RAM is initialized,
+//! so any `.data` or `.bss` static written there would be overwritten
+//! afterwards.  The cache is therefore placed in the `.uninit` section,
+//! which `cortex-m-rt` never initializes, and is marked valid with a magic
+//! word once populated.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use core::ffi::c_void;
+use core::mem::MaybeUninit;
+use core::ptr::{addr_of, addr_of_mut};
+
+use crate::{rom_codes, ROM};
+
+/// The functions cached by [`rom_pre_init`]: reset_usb_boot, memcpy,
+/// memcpy44 and the flash functions
+const CACHED_CODES: [(u8, u8); 9] = [
+    rom_codes::RESET_USB_BOOT,
+    rom_codes::MEMCPY,
+    rom_codes::MEMCPY44,
+    rom_codes::CONNECT_INTERNAL_FLASH,
+    rom_codes::FLASH_EXIT_XIP,
+    rom_codes::FLASH_RANGE_ERASE,
+    rom_codes::FLASH_RANGE_PROGRAM,
+    rom_codes::FLASH_FLUSH_CACHE,
+    rom_codes::FLASH_ENTER_CMD_XIP,
+];
+
+/// Marks the cache as populated - "ROMC"
+const CACHE_VALID: u32 = 0x524F_4D43;
+
+#[repr(C)]
+struct Cache {
+    valid: u32,
+    ptrs: [*mut c_void; CACHED_CODES.len()],
+}
+
+#[cfg_attr(
+    all(target_arch = "arm", target_os = "none"),
+    link_section = ".uninit.rp2040_rom.CACHE"
+)]
+static mut CACHE: MaybeUninit<Cache> = MaybeUninit::uninit();
+
+/// Resolves and caches the most commonly used ROM function pointers, so
+/// later calls skip the ROM table walk.
+///
+/// Cached functions are `reset_usb_boot`, `memcpy`, `memcpy44` and the
+/// flash functions.  Designed to be called from `cortex-m-rt`'s
+/// `#[pre_init]` function, but can be called at any time:
+///
+/// ```rust,ignore
+/// #[cortex_m_rt::pre_init]
+/// unsafe fn pre_init() {
+///     rp2040_rom::rom_pre_init();
+/// }
+/// ```
+///
+/// With the `custom-base` feature, the ROM base is first reset to 0, as the
+/// static holding it hasn't been initialized when `#[pre_init]` runs, so
+/// its value can't be trusted, so call `set_rom_base` after this, not
+/// before.  The cache holds the functions of the ROM at address 0, and is
+/// only used while the base is 0, so a base set later takes effect as
+/// usual.
+///
+/// # Safety
+///
+/// Must only be called on an RP2040, and not concurrently with any other
+/// function in this crate.  The linker script must provide an `.uninit`
+/// section, as `cortex-m-rt`'s does.
+pub unsafe fn rom_pre_init() {
+    let cache = addr_of_mut!(CACHE) as *mut Cache;
+
+    // The base is zeroed again when .bss is initialized, after this
+    #[cfg(feature = "custom-base")]
+    crate::custom_base::set_rom_base(0);
+
+    // Invalidate the cache first, in case the contents of uninitialized RAM
+    // happen to look valid, so lookups below walk the ROM table
+    core::ptr::write_volatile(addr_of_mut!((*cache).valid), 0);
+
+    for (ii, &code) in CACHED_CODES.iter().enumerate() {
+        (*cache).ptrs[ii] = ROM::rom_func_lookup(code);
+    }
+
+    core::ptr::write_volatile(addr_of_mut!((*cache).valid), CACHE_VALID);
+}
+
+// Returns the cached pointer for a function, if rom_pre_init() has been
+// called and the function is one of those cached
+pub(crate) fn cached_func(code: (u8, u8)) -> Option<*mut c_void> {
+    let cache = addr_of!(CACHE) as *const Cache;
+
+    // Safety: the pointers are only read once valid has been set, after
+    // they have been populated
+    unsafe {
+        if core::ptr::read_volatile(addr_of!((*cache).valid)) != CACHE_VALID {
+            return None;
+        }
+        CACHED_CODES
+            .iter()
+            .position(|&cached| cached == code)
+            .map(|ii| (*cache).ptrs[ii])
+    }
+}
+
+// Marks the cache valid, with every cached function at `ptr`, so tests can
+// populate it without walking a ROM
+#[cfg(all(test, feature = "custom-base"))]
+pub(crate) unsafe fn set_cache(ptr: *mut c_void) {
+    let cache = addr_of_mut!(CACHE) as *mut Cache;
+    (*cache).ptrs = [ptr; CACHED_CODES.len()];
+    core::ptr::write_volatile(addr_of_mut!((*cache).valid), CACHE_VALID);
+}
+
+// Marks the cache invalid again
+#[cfg(all(test, feature = "custom-base"))]
+pub(crate) unsafe fn clear_cache() {
+    let cache = addr_of_mut!(CACHE) as *mut Cache;
+    core::ptr::write_volatile(addr_of_mut!((*cache).valid), 0);
+}
@@ -0,0 +1,197 @@
+//! A builder for the [`ROM::reset_usb_boot`] parameters, and resetting with
+//! the watchdog scratch registers cleared.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use crate::ROM;
+
+/// `disable_interface_mask` bits
+const DISABLE_MASS_STORAGE: u32 = 1 << 0;
+const DISABLE_PICOBOOT: u32 = 1 << 1;
+
+/// Returns the `disable_interface_mask` parameter of [`ROM::reset_usb_boot`]
+/// which disables the USB mass storage interface, if `mass_storage`, and the
+/// PICOBOOT interface, if `picoboot`.
+///
+/// Bit 0 disables mass storage and bit 1 PICOBOOT.  Being `const`, it can be
+/// used for static configuration:
+///
+/// ```rust
+/// use rp2040_rom::disable_mask;
+///
+/// const DISABLE: u32 = disable_mask(false, true);
+/// assert_eq!(DISABLE, 0b10);
+/// ```
+pub const fn disable_mask(mass_storage: bool, picoboot: bool) -> u32 {
+    let mut mask = 0;
+    if mass_storage {
+        mask |= DISABLE_MASS_STORAGE;
+    }
+    if picoboot {
+        mask |= DISABLE_PICOBOOT;
+    }
+    mask
+}
+
+/// The GPIOs which exist, and so can be USB activity pins
+#[cfg(not(feature = "rp2350"))]
+const ACTIVITY_PIN_MASK: u32 = (1 << 30) - 1;
+
+// Checks the reset_usb_boot() parameters, in debug builds.
+pub(crate) fn debug_check_reset_masks(gpio_mask: u32, disable_mask: u32) {
+    #[cfg(not(feature = "rp2350"))]
+    debug_check!(
+        gpio_mask & !ACTIVITY_PIN_MASK == 0,
+        "USB activity pin mask has GPIOs above 29"
+    );
+    #[cfg(feature = "rp2350")]
+    let _ = gpio_mask;
+    debug_check!(
+        disable_mask & !(DISABLE_MASS_STORAGE | DISABLE_PICOBOOT) == 0,
+        "unknown disable_interface_mask bits"
+    );
+}
+
+/// Watchdog registers
+#[cfg(not(feature = "rp2350"))]
+const WATCHDOG_BASE: usize = 0x4005_8000;
+#[cfg(feature = "rp2350")]
+const WATCHDOG_BASE: usize = 0x400d_8000;
+const WATCHDOG_SCRATCH0: *mut u32 = (WATCHDOG_BASE + 0x0c) as *mut u32;
+const WATCHDOG_SCRATCH_COUNT: usize = 8;
+
+/// Public reset functions
+impl ROM {
+    /// Resets the device and enters USB bootloader mode, as
+    /// [`ROM::reset_usb_boot`], after zeroing the 8 watchdog scratch
+    /// registers, `SCRATCH0` to `SCRATCH7`.
+    ///
+    /// The scratch registers survive a watchdog reset, and `SCRATCH4` to
+    /// `SCRATCH7` are used by the ROM to select a boot vector, so stale
+    /// values left by a crashed application, or by application code which
+    /// uses them to pass state across a reset, can alter the next boot.
+    /// The registers are written directly, so no PAC is needed.
+    ///
+    /// # Parameters
+    ///
+    /// As for [`ROM::reset_usb_boot`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::reset_usb_boot`].  Anything else relying on the scratch
+    /// registers loses its contents.
+    pub unsafe fn reset_usb_boot_clean(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+    ) -> ! {
+        for index in 0..WATCHDOG_SCRATCH_COUNT {
+            core::ptr::write_volatile(WATCHDOG_SCRATCH0.add(index), 0);
+        }
+
+        Self::reset_usb_boot(usb_activity_gpio_pin_mask, disable_interface_mask)
+    }
+}
+
+/// Builds the parameters for [`ROM::reset_usb_boot`], so the call site says
+/// what it means rather than passing two bitmasks.
+///
+/// ```rust,ignore
+/// unsafe {
+///     BootloaderReset::new()
+///         .activity_pin(25)
+///         .disable_picoboot()
+///         .execute()
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BootloaderReset {
+    gpio_mask: u32,
+    disable_mask: u32,
+}
+
+impl BootloaderReset {
+    /// Creates a builder with no activity pin, and both USB interfaces
+    /// enabled.
+    pub const fn new() -> Self {
+        Self {
+            gpio_mask: 0,
+            disable_mask: 0,
+        }
+    }
+
+    /// Adds a GPIO pin to show USB activity on, such as an LED.
+    ///
+    /// On the RP2350 only a single pin is supported, so the lowest pin set
+    /// is used.
+    ///
+    /// # Panics
+    ///
+    /// If `pin` is 32 or more.
+    pub const fn activity_pin(mut self, pin: u8) -> Self {
+        assert!(pin < 32, "activity pin must be less than 32");
+        self.gpio_mask |= 1 << pin;
+        self
+    }
+
+    /// Disables the USB mass storage interface, so the device doesn't appear
+    /// as a drive.
+    pub const fn disable_mass_storage(mut self) -> Self {
+        self.disable_mask |= DISABLE_MASS_STORAGE;
+        self
+    }
+
+    /// Disables the PICOBOOT interface, used by `picotool`.
+    pub const fn disable_picoboot(mut self) -> Self {
+        self.disable_mask |= DISABLE_PICOBOOT;
+        self
+    }
+
+    /// Returns the `usb_activity_gpio_pin_mask` parameter.
+    pub const fn gpio_mask(&self) -> u32 {
+        self.gpio_mask
+    }
+
+    /// Returns the `disable_interface_mask` parameter.
+    pub const fn disable_mask(&self) -> u32 {
+        self.disable_mask
+    }
+
+    /// Resets the device into USB bootloader mode, with
+    /// [`ROM::reset_usb_boot`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::reset_usb_boot`].
+    pub unsafe fn execute(self) -> ! {
+        ROM::reset_usb_boot(self.gpio_mask, self.disable_mask)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_sets_masks() {
+        const RESET: BootloaderReset = BootloaderReset::new()
+            .activity_pin(25)
+            .activity_pin(3)
+            .disable_picoboot();
+        assert_eq!(RESET.gpio_mask(), (1 << 25) | (1 << 3));
+        assert_eq!(RESET.disable_mask(), 0b10);
+
+        let reset = BootloaderReset::new().disable_mass_storage();
+        assert_eq!(reset.gpio_mask(), 0);
+        assert_eq!(reset.disable_mask(), 0b01);
+    }
+
+    #[test]
+    fn disable_mask_bits() {
+        assert_eq!(disable_mask(false, false), 0);
+        assert_eq!(disable_mask(true, false), 1);
+        assert_eq!(disable_mask(false, true), 2);
+        assert_eq!(disable_mask(true, true), 3);
+    }
+}
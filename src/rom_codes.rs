@@ -0,0 +1,90 @@
+//! The two character codes of the known ROM functions and data, as used by
+//! [`ROM::rom_func_lookup`](crate::ROM::rom_func_lookup) and
+//! [`ROM::rom_data_lookup`](crate::ROM::rom_data_lookup).
+//!
+//! The codes are from the RP2040 datasheet, except where marked as RP2350
+//! only.  Not every code is present in every ROM version - check with
+//! [`ROM::func_exists`](crate::ROM::func_exists).
+//!
+//! The data codes below are the whole of the RP2040 ROM's data table.  In
+//! particular, the ROM holds no table of GPIO function names, so diagnostic
+//! tools must take those from the datasheet's GPIO function table.
+//!
+//! Likewise the function codes are the whole of the RP2040 ROM's public
+//! function table, in every ROM version.  There is no clock measurement
+//! function, for example, so the ring oscillator frequency must be measured
+//! with the clocks block's frequency counter (`FC0`), against a known
+//! reference.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+/// `popcount32` function
+pub const POPCOUNT32: (u8, u8) = (b'P', b'3');
+
+/// `reverse32` function
+pub const REVERSE32: (u8, u8) = (b'R', b'3');
+
+/// `clz32` function
+pub const CLZ32: (u8, u8) = (b'L', b'3');
+
+/// `ctz32` function
+pub const CTZ32: (u8, u8) = (b'T', b'3');
+
+/// `memset` function
+pub const MEMSET: (u8, u8) = (b'M', b'S');
+
+/// `memset4` function
+pub const MEMSET4: (u8, u8) = (b'S', b'4');
+
+/// `memcpy` function
+pub const MEMCPY: (u8, u8) = (b'M', b'C');
+
+/// `memcpy44` function
+pub const MEMCPY44: (u8, u8) = (b'C', b'4');
+
+/// `reset_usb_boot` function
+pub const RESET_USB_BOOT: (u8, u8) = (b'U', b'B');
+
+/// `connect_internal_flash` function
+pub const CONNECT_INTERNAL_FLASH: (u8, u8) = (b'I', b'F');
+
+/// `flash_exit_xip` function
+pub const FLASH_EXIT_XIP: (u8, u8) = (b'E', b'X');
+
+/// `flash_range_erase` function
+pub const FLASH_RANGE_ERASE: (u8, u8) = (b'R', b'E');
+
+/// `flash_range_program` function
+pub const FLASH_RANGE_PROGRAM: (u8, u8) = (b'R', b'P');
+
+/// `flash_flush_cache` function
+pub const FLASH_FLUSH_CACHE: (u8, u8) = (b'F', b'C');
+
+/// `flash_enter_cmd_xip` function
+pub const FLASH_ENTER_CMD_XIP: (u8, u8) = (b'C', b'X');
+
+/// `debug_trampoline` function
+pub const DEBUG_TRAMPOLINE: (u8, u8) = (b'D', b'T');
+
+/// `debug_trampoline_end` function
+pub const DEBUG_TRAMPOLINE_END: (u8, u8) = (b'D', b'E');
+
+/// `wait_for_vector` function, the core 1 launch loop
+pub const WAIT_FOR_VECTOR: (u8, u8) = (b'W', b'V');
+
+/// `reboot` function.  RP2350 only.
+pub const REBOOT: (u8, u8) = (b'R', b'B');
+
+/// Copyright string data, a null terminated string
+pub const COPYRIGHT: (u8, u8) = (b'C', b'R');
+
+/// Git revision data, a `u32`
+pub const GIT_REVISION: (u8, u8) = (b'G', b'R');
+
+/// Single precision soft-float table data
+pub const SOFT_FLOAT_TABLE: (u8, u8) = (b'S', b'F');
+
+/// Double precision soft-float table data.  Version 2 ROMs and later.
+pub const SOFT_DOUBLE_TABLE: (u8, u8) = (b'S', b'D');
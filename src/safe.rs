@@ -0,0 +1,200 @@
+//! Safe wrappers for the ROM's pure functions, unlocked by a [`RomHandle`].
+//!
+//! The bit manipulation and soft-float functions have no side effects: their
+//! only safety requirement is that this really is an RP2040 ROM, so that the
+//! function lookups find the right code.  [`ROM::init`] checks that once,
+//! with [`ROM::platform_check`], and returns a [`RomHandle`], a zero-sized
+//! token which each function here takes, by reference, as its first
+//! argument.  Passing it costs nothing, and as it can only be had from
+//! [`ROM::init`], the functions can't be called before the platform has been
+//! checked - an easy mistake in early initialisation code.
+//!
+//! ```rust,ignore
+//! use rp2040_rom::{safe, ROM};
+//!
+//! let rom = ROM::init().expect("not an RP2040");
+//! let bits = safe::popcount32(&rom, 0xF0);
+//! let sum = safe::float_add(&rom, 1.5, 2.25);
+//! ```
+//!
+//! As with [`ROM`] itself, each call looks its function up.  For hot loops,
+//! `BitOps` and `FloatOps`, with the `bit-ops` and `soft-float` features,
+//! look their functions up once.  These functions are not available with
+//! the `rp2350` feature, as that ROM has neither group of functions.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+use core::cmp::Ordering;
+
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+use crate::float::{SF_TABLE_FADD, SF_TABLE_FDIV, SF_TABLE_FMUL, SF_TABLE_FSQRT, SF_TABLE_FSUB};
+#[cfg(all(feature = "bit-ops", not(feature = "rp2350")))]
+use crate::rom_codes;
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+use crate::RomError;
+use crate::{PlatformError, ROM};
+
+/// Proof that [`ROM::platform_check`] has passed, so the functions in
+/// [`safe`](self) can be called safely.
+///
+/// Zero-sized, and obtained only from [`ROM::init`].
+#[derive(Debug, Clone, Copy)]
+pub struct RomHandle(());
+
+/// Public safe handle functions
+impl ROM {
+    /// Checks the ROM magic and version, and that the functions present in
+    /// every supported ROM version can be found, as
+    /// [`ROM::platform_check`], and returns a [`RomHandle`] unlocking the
+    /// [`safe`](crate::safe) functions.
+    ///
+    /// Call once at startup, and pass the handle to the code which needs
+    /// it.  It is `Copy`, so can be stored freely.
+    ///
+    /// # Returns
+    ///
+    /// The handle, or the first platform check to fail.
+    pub fn init() -> Result<RomHandle, PlatformError> {
+        Self::platform_check()?;
+        Ok(RomHandle(()))
+    }
+
+    /// As [`ROM::init`].
+    #[inline]
+    pub fn safe_handle() -> Result<RomHandle, PlatformError> {
+        Self::init()
+    }
+}
+
+#[cfg(all(feature = "bit-ops", not(feature = "rp2350")))]
+// Call a ROM bit manipulation function, present in every RP2040 ROM version
+fn bit_func(code: (u8, u8), x: u32) -> u32 {
+    type RomBitFn = unsafe extern "C" fn(u32) -> u32;
+
+    // Safety: a RomHandle exists, so this is an RP2040 ROM, which has every
+    // bit manipulation function, with this signature
+    unsafe {
+        let func: RomBitFn = core::mem::transmute(ROM::rom_func_lookup(code));
+        func(x)
+    }
+}
+
+/// Returns the number of bits set in `x`.
+#[cfg(all(feature = "bit-ops", not(feature = "rp2350")))]
+pub fn popcount32(_rom: &RomHandle, x: u32) -> u32 {
+    bit_func(rom_codes::POPCOUNT32, x)
+}
+
+/// Returns `x` with its bits in reverse order.
+#[cfg(all(feature = "bit-ops", not(feature = "rp2350")))]
+pub fn reverse32(_rom: &RomHandle, x: u32) -> u32 {
+    bit_func(rom_codes::REVERSE32, x)
+}
+
+/// Returns the number of leading zero bits in `x`, or 32 if `x` is 0.
+#[cfg(all(feature = "bit-ops", not(feature = "rp2350")))]
+pub fn clz32(_rom: &RomHandle, x: u32) -> u32 {
+    bit_func(rom_codes::CLZ32, x)
+}
+
+/// Returns the number of trailing zero bits in `x`, or 32 if `x` is 0.
+#[cfg(all(feature = "bit-ops", not(feature = "rp2350")))]
+pub fn ctz32(_rom: &RomHandle, x: u32) -> u32 {
+    bit_func(rom_codes::CTZ32, x)
+}
+
+// The soft-float functions.  Safety, for each: a RomHandle exists, so this
+// is an RP2040 ROM, whose soft-float table every ROM function relies on.
+
+/// Returns `a + b`.
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn float_add(_rom: &RomHandle, a: f32, b: f32) -> f32 {
+    unsafe { ROM::sf_binary(SF_TABLE_FADD, a, b) }
+}
+
+/// Returns `a - b`.
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn float_sub(_rom: &RomHandle, a: f32, b: f32) -> f32 {
+    unsafe { ROM::sf_binary(SF_TABLE_FSUB, a, b) }
+}
+
+/// Returns `a * b`.
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn float_mul(_rom: &RomHandle, a: f32, b: f32) -> f32 {
+    unsafe { ROM::sf_binary(SF_TABLE_FMUL, a, b) }
+}
+
+/// Returns `a / b`.  See [`ROM::float_div_checked`] for division by zero.
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn float_div(_rom: &RomHandle, a: f32, b: f32) -> f32 {
+    unsafe { ROM::sf_binary(SF_TABLE_FDIV, a, b) }
+}
+
+/// Returns the square root of `x`.
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn float_sqrt(_rom: &RomHandle, x: f32) -> f32 {
+    unsafe { ROM::sf_unary(SF_TABLE_FSQRT, x) }
+}
+
+/// As [`ROM::float_to_int`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn float_to_int(_rom: &RomHandle, x: f32) -> i32 {
+    unsafe { ROM::float_to_int(x) }
+}
+
+/// As [`ROM::int_to_float`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn int_to_float(_rom: &RomHandle, x: i32) -> f32 {
+    unsafe { ROM::int_to_float(x) }
+}
+
+/// As [`ROM::uint_to_float`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn uint_to_float(_rom: &RomHandle, x: u32) -> f32 {
+    unsafe { ROM::uint_to_float(x) }
+}
+
+/// As [`ROM::fcmp`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn fcmp(_rom: &RomHandle, a: f32, b: f32) -> Result<Ordering, RomError> {
+    unsafe { ROM::fcmp(a, b) }
+}
+
+/// As [`ROM::fsin`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn fsin(_rom: &RomHandle, x: f32) -> f32 {
+    unsafe { ROM::fsin(x) }
+}
+
+/// As [`ROM::fcos`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn fcos(_rom: &RomHandle, x: f32) -> f32 {
+    unsafe { ROM::fcos(x) }
+}
+
+/// As [`ROM::ftan`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn ftan(_rom: &RomHandle, x: f32) -> f32 {
+    unsafe { ROM::ftan(x) }
+}
+
+/// As [`ROM::fexp`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn fexp(_rom: &RomHandle, x: f32) -> f32 {
+    unsafe { ROM::fexp(x) }
+}
+
+/// As [`ROM::fln`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn fln(_rom: &RomHandle, x: f32) -> f32 {
+    unsafe { ROM::fln(x) }
+}
+
+/// As [`ROM::fatan2`].
+#[cfg(all(feature = "soft-float", not(feature = "rp2350")))]
+pub fn fatan2(_rom: &RomHandle, y: f32, x: f32) -> Result<f32, RomError> {
+    unsafe { ROM::fatan2(y, x) }
+}
@@ -0,0 +1,106 @@
+//! Iteration over the RP2040 ROM lookup tables.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use crate::{BOOTROM_DATA_TABLE_OFFSET, BOOTROM_FUNC_TABLE_OFFSET, ROM};
+
+/// An iterator over the entries of a ROM lookup table, yielding
+/// `(code, pointer)` pairs.
+///
+/// Each entry in an RP2040 ROM table is a 16-bit code (the two characters
+/// packed as by [`ROM::rom_table_code`]) followed by a 16-bit pointer.  The
+/// table is terminated by an entry with a code of zero.
+///
+/// This is intended for debugging and introspection, for example to list
+/// the functions present in the current ROM version.
+#[derive(Debug)]
+pub struct RomTableIter {
+    pos: *const u16,
+}
+
+impl RomTableIter {
+    /// Creates an iterator over a ROM format lookup table.
+    ///
+    /// # Safety
+    ///
+    /// `table` must point to a valid table, readable up to and including its
+    /// zero terminating code.
+    pub unsafe fn new(table: *const u16) -> Self {
+        Self { pos: table }
+    }
+}
+
+impl Iterator for RomTableIter {
+    type Item = (u16, *mut core::ffi::c_void);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Safety: new()'s caller guaranteed the table is readable up to the
+        // terminator, and we never advance beyond it
+        unsafe {
+            let code = core::ptr::read_volatile(self.pos);
+            if code == 0 {
+                return None;
+            }
+            let ptr = core::ptr::read_volatile(self.pos.add(1));
+            self.pos = self.pos.add(2);
+
+            Some((code, ptr as usize as *mut core::ffi::c_void))
+        }
+    }
+}
+
+/// Public table functions
+impl ROM {
+    /// Returns an iterator over every entry in the ROM function table.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// use rp2040_rom::ROM;
+    ///
+    /// for (code, ptr) in unsafe { ROM::func_table_iter() } {
+    ///     let [c1, c2] = code.to_le_bytes();
+    ///     // c1 and c2 are the two character code for the function at ptr
+    /// }
+    /// ```
+    pub unsafe fn func_table_iter() -> RomTableIter {
+        let table = Self::rom_hword_as_ptr(BOOTROM_FUNC_TABLE_OFFSET) as *const u16;
+        RomTableIter::new(table)
+    }
+
+    /// Returns an iterator over every entry in the ROM data table, such as
+    /// the copyright string (`CR`), git revision (`GR`) and soft-float tables
+    /// (`SF` and `SD`).
+    ///
+    /// The pointers are to the data itself - see [`ROM::rom_data_lookup`].
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn data_table_iter() -> RomTableIter {
+        let table = Self::rom_hword_as_ptr(BOOTROM_DATA_TABLE_OFFSET) as *const u16;
+        RomTableIter::new(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn iter_stops_at_terminator() {
+        let table: [u16; 6] = [0x4255, 0x1234, 0x3350, 0x5678, 0, 0xFFFF];
+        let entries: [(u16, usize); 2] = [(0x4255, 0x1234), (0x3350, 0x5678)];
+
+        let iter = unsafe { RomTableIter::new(table.as_ptr()) };
+        assert!(iter
+            .map(|(code, ptr)| (code, ptr as usize))
+            .eq(entries.iter().copied()));
+    }
+}
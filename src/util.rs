@@ -0,0 +1,478 @@
+//! Reset and ROM information functions, including a separate namespace for
+//! them and a plain text ROM description.
+
+// Copyright (c) 2025 Piers Finlayson <piers@piers.rocks>
+//
+// MIT licensed - see https://opensource.org/licenses/MIT
+
+use core::ffi::CStr;
+use core::fmt::Write;
+
+use crate::{rom_codes, ResetFailed, RomFunctionReport, BOOTROM_MAGIC, ROM};
+
+/// The size of the bootrom, in bytes
+#[cfg(not(feature = "rp2350"))]
+const BOOTROM_SIZE: usize = 16 * 1024;
+#[cfg(feature = "rp2350")]
+const BOOTROM_SIZE: usize = 32 * 1024;
+
+/// Errors returned by [`ROM::platform_check`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PlatformError {
+    /// The ROM magic isn't as expected, so this isn't the expected chip, or
+    /// the ROM isn't mapped where expected.  Contains the 3 magic bytes
+    /// found, little endian.
+    BadMagic { found: u32 },
+
+    /// The ROM version isn't one this crate supports.  Contains the version
+    /// found.
+    UnsupportedVersion(u8),
+
+    /// A function present in every supported ROM version is missing.
+    /// Contains the function's code, packed as by [`ROM::rom_table_code`].
+    FunctionNotFound { code: u16 },
+}
+
+/// The ROM table ABI, as returned by [`ROM::rom_abi`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RomAbi {
+    /// Separate function and data tables, searched with
+    /// `rom_table_lookup(table, code)`
+    Rp2040,
+
+    /// A single table, searched with `rom_table_lookup(code, flags)`
+    #[cfg(feature = "rp2350")]
+    Rp2350,
+}
+
+/// Public ROM information functions
+impl ROM {
+    /// Writes a human readable description of the ROM into `buf`: whether
+    /// the magic is valid, the version, the git revision in hex, and the
+    /// copyright string.  Lines are separated by `\n`.
+    ///
+    /// Doesn't need any formatting infrastructure, so is suitable for
+    /// printing over a UART or USB CDC console.  The git revision and
+    /// copyright lines are omitted if the ROM doesn't have them.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes written.  If `buf` is too small, the description
+    /// is truncated to fit.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn describe(buf: &mut [u8]) -> usize {
+        let mut writer = TruncatingWriter { buf, pos: 0 };
+        let magic = if Self::rom_magic_valid() {
+            "valid"
+        } else {
+            "invalid"
+        };
+
+        // TruncatingWriter never fails, so there are no errors to handle
+        let _ = writeln!(writer, "ROM magic: {magic}");
+        let _ = writeln!(writer, "ROM version: {}", Self::rom_version());
+
+        let git_revision = Self::rom_data_ptr(rom_codes::GIT_REVISION);
+        if !git_revision.is_null() {
+            let _ = writeln!(writer, "Git revision: {:#010x}", *git_revision);
+        }
+
+        let copyright = Self::copyright_string();
+        if !copyright.is_empty() {
+            let _ = writeln!(writer, "Copyright: {}", copyright.to_str().unwrap_or("?"));
+        }
+
+        writer.pos
+    }
+
+    /// Copies the bootrom into `buf`, for integrity checks or offline
+    /// analysis.
+    ///
+    /// Copies as much of the ROM as fits, from address 0, using volatile
+    /// reads.  The ROM is 16KB on the RP2040, and 32KB on the RP2350.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes copied, less than the ROM size if `buf` is too
+    /// small to hold it.
+    ///
+    /// # Safety
+    ///
+    /// Must only be called on an RP2040.
+    pub unsafe fn copy_bootrom(buf: &mut [u8]) -> usize {
+        let len = buf.len().min(BOOTROM_SIZE);
+        let rom = Self::rom_base() as *const u8;
+        for (index, byte) in buf[..len].iter_mut().enumerate() {
+            // Volatile reads may access memory outside any Rust allocation,
+            // such as the ROM at address 0
+            *byte = core::ptr::read_volatile(rom.wrapping_add(index));
+        }
+
+        len
+    }
+
+    /// Calculates the CRC-32 (IEEE 802.3, as used by zlib) of `data`,
+    /// continuing from `init`, which is 0 for the first, or only, call.
+    ///
+    /// Neither the RP2040 nor the RP2350 ROM exports a CRC-32 function, so
+    /// this is calculated in software - but bit by bit, to avoid the 1KB flash
+    /// cost of a lookup table.  Shifting right processes the reflected bit
+    /// order directly, so the ROM's `reverse32` isn't needed.  See [`Crc32`]
+    /// to accumulate the CRC over several calls.
+    ///
+    /// This isn't the CRC the ROM checks boot2 with, which is the unreflected
+    /// CRC-32/MPEG-2 - see `ROM::validate_boot2`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use rp2040_rom::ROM;
+    ///
+    /// let crc = ROM::crc32(b"12345", 0);
+    /// assert_eq!(ROM::crc32(b"6789", crc), ROM::crc32(b"123456789", 0));
+    /// ```
+    pub fn crc32(data: &[u8], init: u32) -> u32 {
+        let mut crc = !init;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+        !crc
+    }
+}
+
+/// Accumulates the CRC-32 of data supplied in pieces, as [`ROM::crc32`].
+///
+/// ```
+/// use rp2040_rom::{Crc32, ROM};
+///
+/// let mut crc = Crc32::new();
+/// crc.update(b"12345");
+/// crc.update(b"6789");
+/// assert_eq!(crc.finish(), ROM::crc32(b"123456789", 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Crc32 {
+    crc: u32,
+}
+
+impl Crc32 {
+    /// Creates an accumulator, with no data.
+    pub const fn new() -> Self {
+        Self { crc: 0 }
+    }
+
+    /// Adds `data` to the CRC.
+    pub fn update(&mut self, data: &[u8]) {
+        self.crc = ROM::crc32(data, self.crc);
+    }
+
+    /// Returns the CRC-32 of all the data added so far.
+    pub const fn finish(&self) -> u32 {
+        self.crc
+    }
+}
+
+/// Public chip revision and platform check functions
+impl ROM {
+    /// Returns the RP2040 chip revision, from the ROM version byte at address
+    /// `0x13`, which follows the 3 byte magic at `0x10`.
+    ///
+    /// Each chip revision has its own ROM, so the version identifies the
+    /// revision:
+    ///
+    /// | Value | Revision |
+    /// |-------|----------|
+    /// | 1     | B0       |
+    /// | 2     | B1       |
+    /// | 3     | B2       |
+    #[cfg(not(feature = "rp2350"))]
+    pub fn chip_revision() -> u8 {
+        Self::rom_version()
+    }
+
+    /// Returns the ROM table ABI of the chip this is running on, so code
+    /// built for more than one part can choose the lookup behaviour at
+    /// runtime.
+    ///
+    /// With the `rp2350` feature, the third magic byte, 1 on the RP2040 and
+    /// 2 on the RP2350, tells the two apart.  Without it, this always
+    /// returns [`RomAbi::Rp2040`].  Use [`ROM::platform_check`] to check the
+    /// magic is valid at all.
+    pub fn rom_abi() -> RomAbi {
+        #[cfg(not(feature = "rp2350"))]
+        {
+            RomAbi::Rp2040
+        }
+        #[cfg(feature = "rp2350")]
+        if Self::rom_magic()[2] == 0x01 {
+            RomAbi::Rp2040
+        } else {
+            RomAbi::Rp2350
+        }
+    }
+
+    /// Checks this is the expected chip and ROM, by checking the ROM magic
+    /// and version, and that a handful of functions present in every ROM
+    /// version can be found.
+    ///
+    /// Call once at startup to catch running on the wrong chip, in an
+    /// emulator, or with a corrupted ROM, before any other ROM function
+    /// faults.
+    ///
+    /// # Returns
+    ///
+    /// `Ok` if the checks pass, otherwise the first check to fail.
+    pub fn platform_check() -> Result<(), PlatformError> {
+        // Functions probed - present in every supported ROM version
+        #[cfg(not(feature = "rp2350"))]
+        const SENTINEL_FUNCS: [(u8, u8); 4] = [
+            rom_codes::RESET_USB_BOOT,
+            rom_codes::POPCOUNT32,
+            rom_codes::MEMCPY,
+            rom_codes::CONNECT_INTERNAL_FLASH,
+        ];
+        #[cfg(feature = "rp2350")]
+        const SENTINEL_FUNCS: [(u8, u8); 2] =
+            [rom_codes::REBOOT, rom_codes::CONNECT_INTERNAL_FLASH];
+
+        // Supported ROM versions
+        #[cfg(not(feature = "rp2350"))]
+        const SUPPORTED_VERSIONS: core::ops::RangeInclusive<u8> = 1..=3;
+        #[cfg(feature = "rp2350")]
+        const SUPPORTED_VERSIONS: core::ops::RangeInclusive<u8> = 1..=u8::MAX;
+
+        let magic = Self::rom_magic();
+        if magic != BOOTROM_MAGIC {
+            let found = u32::from_le_bytes([magic[0], magic[1], magic[2], 0]);
+            return Err(PlatformError::BadMagic { found });
+        }
+
+        let version = Self::rom_version();
+        if !SUPPORTED_VERSIONS.contains(&version) {
+            return Err(PlatformError::UnsupportedVersion(version));
+        }
+
+        for (c1, c2) in SENTINEL_FUNCS {
+            if !Self::func_exists((c1, c2)) {
+                let code = Self::rom_table_code(c1, c2) as u16;
+                return Err(PlatformError::FunctionNotFound { code });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Writes into a buffer, silently discarding anything which doesn't fit
+struct TruncatingWriter<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Write for TruncatingWriter<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let len = s.len().min(self.buf.len() - self.pos);
+        self.buf[self.pos..self.pos + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.pos += len;
+        Ok(())
+    }
+}
+
+/// The reset and ROM information functions, on their own.
+///
+/// A zero-sized namespace containing only the reset and ROM information
+/// functions of [`ROM`], which it calls.  See also `RomFlash` and `RomMath`,
+/// with the `flash-ops` and `soft-float` features.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RomUtil;
+
+/// Public reset and ROM information functions
+impl RomUtil {
+    /// See [`ROM::reset_usb_boot`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::reset_usb_boot`].
+    #[inline]
+    pub unsafe fn reset_usb_boot(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+    ) -> ! {
+        ROM::reset_usb_boot(usb_activity_gpio_pin_mask, disable_interface_mask)
+    }
+
+    /// See [`ROM::reset_usb_boot_clean`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::reset_usb_boot_clean`].
+    #[inline]
+    pub unsafe fn reset_usb_boot_clean(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+    ) -> ! {
+        ROM::reset_usb_boot_clean(usb_activity_gpio_pin_mask, disable_interface_mask)
+    }
+
+    /// See [`ROM::try_reset_usb_boot_returning`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::try_reset_usb_boot_returning`].
+    #[inline]
+    pub unsafe fn try_reset_usb_boot_returning(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+    ) -> ResetFailed {
+        ROM::try_reset_usb_boot_returning(usb_activity_gpio_pin_mask, disable_interface_mask)
+    }
+
+    /// See [`ROM::reset_usb_boot_or`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::reset_usb_boot_or`].
+    #[inline]
+    pub unsafe fn reset_usb_boot_or(
+        usb_activity_gpio_pin_mask: u32,
+        disable_interface_mask: u32,
+        on_return: fn() -> !,
+    ) -> ! {
+        ROM::reset_usb_boot_or(
+            usb_activity_gpio_pin_mask,
+            disable_interface_mask,
+            on_return,
+        )
+    }
+
+    /// See [`ROM::copyright_string`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::copyright_string`].
+    #[inline]
+    pub unsafe fn copyright_string() -> &'static CStr {
+        ROM::copyright_string()
+    }
+
+    /// See [`ROM::describe`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::describe`].
+    #[inline]
+    pub unsafe fn describe(buf: &mut [u8]) -> usize {
+        ROM::describe(buf)
+    }
+
+    /// See [`ROM::copy_bootrom`].
+    ///
+    /// # Safety
+    ///
+    /// As for [`ROM::copy_bootrom`].
+    #[inline]
+    pub unsafe fn copy_bootrom(buf: &mut [u8]) -> usize {
+        ROM::copy_bootrom(buf)
+    }
+
+    /// See [`ROM::chip_revision`].
+    #[cfg(not(feature = "rp2350"))]
+    #[inline]
+    pub fn chip_revision() -> u8 {
+        ROM::chip_revision()
+    }
+
+    /// See [`ROM::rom_abi`].
+    #[inline]
+    pub fn rom_abi() -> RomAbi {
+        ROM::rom_abi()
+    }
+
+    /// See [`ROM::platform_check`].
+    #[inline]
+    pub fn platform_check() -> Result<(), PlatformError> {
+        ROM::platform_check()
+    }
+
+    /// See [`ROM::init`].
+    #[inline]
+    pub fn init() -> Result<crate::RomHandle, PlatformError> {
+        ROM::init()
+    }
+
+    /// See [`ROM::safe_handle`].
+    #[inline]
+    pub fn safe_handle() -> Result<crate::RomHandle, PlatformError> {
+        ROM::safe_handle()
+    }
+
+    /// See [`ROM::crc32`].
+    #[inline]
+    pub fn crc32(data: &[u8], init: u32) -> u32 {
+        ROM::crc32(data, init)
+    }
+
+    /// See [`ROM::func_exists`].
+    #[inline]
+    pub fn func_exists(code: (u8, u8)) -> bool {
+        ROM::func_exists(code)
+    }
+
+    /// See [`ROM::available_functions`].
+    #[inline]
+    pub fn available_functions() -> RomFunctionReport {
+        ROM::available_functions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(ROM::crc32(b"123456789", 0), 0xCBF4_3926);
+        assert_eq!(ROM::crc32(&[], 0), 0);
+    }
+
+    #[test]
+    fn crc32_accumulates_in_pieces() {
+        let mut crc = Crc32::new();
+        for piece in [&b"1"[..], b"", b"2345678", b"9"] {
+            crc.update(piece);
+        }
+        assert_eq!(crc.finish(), 0xCBF4_3926);
+        assert_eq!(Crc32::new().finish(), 0);
+    }
+
+    #[test]
+    fn truncating_writer_truncates() {
+        let mut buf = [0u8; 8];
+        let mut writer = TruncatingWriter {
+            buf: &mut buf,
+            pos: 0,
+        };
+        write!(writer, "ROM version: {}", 3).unwrap();
+        assert_eq!(writer.pos, 8);
+        assert_eq!(&buf, b"ROM vers");
+    }
+
+    #[test]
+    fn truncating_writer_empty_buffer() {
+        let mut writer = TruncatingWriter {
+            buf: &mut [],
+            pos: 0,
+        };
+        writeln!(writer, "ROM magic: valid").unwrap();
+        assert_eq!(writer.pos, 0);
+    }
+}